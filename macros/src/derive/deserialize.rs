@@ -0,0 +1,70 @@
+use proc_macro::{Ident, Literal, Span, TokenStream, quote};
+
+use super::{field_ident, impl_generics, respan};
+use crate::struct_def::StructDef;
+
+/// Generates an implementation of `iovera::JsonDeserialize`
+/// which reads a JSON object into the struct field by field.
+///
+/// Every field gets an `Option` slot filled while walking the object's keys,
+/// a key without a field is an error and absent fields are handed to `JsonDeserialize::missing`.
+/// Duplicate keys are already dealt with by `Parser::next_key`, a later value overwrites the slot.
+pub(crate) fn derive_deserialize(def: &StructDef) -> TokenStream {
+    let name = Ident::new(def.name(), Span::call_site());
+    let (impl_gens, ty_gens) = impl_generics(def, quote!(::iovera::JsonDeserialize));
+
+    let mut slots = TokenStream::new();
+    let mut arms = TokenStream::new();
+    let mut inits = TokenStream::new();
+
+    for (num, field) in def.fields().iter().enumerate() {
+        let (ident, key) = field_ident(field);
+        let key = Literal::string(&key);
+        let slot = Ident::new(&format!("field_{num}"), Span::mixed_site());
+
+        slots.extend(quote! {
+            let mut $slot = ::std::option::Option::None;
+        });
+
+        arms.extend(quote! {
+            $key => {
+                $slot = ::std::option::Option::Some(::iovera::JsonDeserialize::deserialize(parser)?);
+            }
+        });
+
+        inits.extend(quote! {
+            $ident: match $slot {
+                ::std::option::Option::Some(val) => val,
+                ::std::option::Option::None => ::iovera::JsonDeserialize::missing(parser, $key, span)?,
+            },
+        });
+    }
+
+    let tokens = quote! {
+        impl $impl_gens ::iovera::JsonDeserialize for $name $ty_gens {
+            fn deserialize(
+                parser: &mut ::iovera::Parser<'_>,
+            ) -> ::std::result::Result<Self, ::iovera::Error> {
+                let span = parser.begin_object()?;
+                $slots
+
+                let mut first = true;
+                while let ::std::option::Option::Some((key, key_span)) = parser.next_key(first)? {
+                    first = false;
+
+                    match key.as_str() {
+                        $arms
+                        _ => {
+                            let kind = ::iovera::ErrorKind::UnknownField(key);
+                            return ::std::result::Result::Err(parser.error(kind, key_span));
+                        }
+                    }
+                }
+
+                ::std::result::Result::Ok(Self { $inits })
+            }
+        }
+    };
+
+    respan(tokens, Span::mixed_site())
+}
@@ -0,0 +1,99 @@
+pub(crate) mod deserialize;
+pub(crate) mod serialize;
+
+use proc_macro::{Group, Ident, Punct, Spacing, Span, TokenStream, TokenTree};
+
+use crate::struct_def::{Field, StructDef};
+
+/// Sets the span of every token in `stream`, groups included.
+pub(crate) fn respan(stream: TokenStream, span: Span) -> TokenStream {
+    stream
+        .into_iter()
+        .map(|token| match token {
+            TokenTree::Group(gr) => {
+                let mut group = Group::new(gr.delimiter(), respan(gr.stream(), span));
+                group.set_span(span);
+
+                TokenTree::Group(group)
+            }
+
+            mut token => {
+                token.set_span(span);
+                token
+            }
+        })
+        .collect()
+}
+
+/// Builds the generics of an `impl` block for `def`
+/// like `<'a, T: Bound + $bound>` along with the ones of the type like `<'a, T>`.
+/// Both are empty if the struct has no generics.
+pub(crate) fn impl_generics(def: &StructDef, bound: TokenStream) -> (TokenStream, TokenStream) {
+    let mut impl_gens: Vec<TokenStream> = Vec::new();
+    let mut ty_gens: Vec<TokenStream> = Vec::new();
+
+    for lifetime in def.lifetimes() {
+        let lifetime: TokenStream = [
+            TokenTree::Punct(Punct::new('\'', Spacing::Joint)),
+            TokenTree::Ident(Ident::new(lifetime.name(), Span::mixed_site())),
+        ]
+        .into_iter()
+        .collect();
+
+        impl_gens.push(lifetime.clone());
+        ty_gens.push(lifetime);
+    }
+
+    for generic in def.generics() {
+        let mut name = generic.name().clone();
+        name.set_span(Span::mixed_site());
+
+        let mut bounded = TokenStream::new();
+        bounded.extend([
+            TokenTree::Ident(name.clone()),
+            TokenTree::Punct(Punct::new(':', Spacing::Alone)),
+        ]);
+
+        for tr in generic.traits() {
+            bounded.extend([
+                TokenTree::Ident(tr.clone()),
+                TokenTree::Punct(Punct::new('+', Spacing::Alone)),
+            ]);
+        }
+
+        bounded.extend(bound.clone());
+
+        impl_gens.push(bounded);
+        ty_gens.push(TokenTree::Ident(name).into());
+    }
+
+    (angle_bracketed(impl_gens), angle_bracketed(ty_gens))
+}
+
+// `<a, b, c>`, or nothing for no items.
+fn angle_bracketed(items: Vec<TokenStream>) -> TokenStream {
+    let mut stream = TokenStream::new();
+
+    if items.is_empty() {
+        return stream;
+    }
+
+    stream.extend_one(TokenTree::Punct(Punct::new('<', Spacing::Alone)));
+
+    for item in items {
+        stream.extend(item);
+        stream.extend_one(TokenTree::Punct(Punct::new(',', Spacing::Alone)));
+    }
+
+    stream.extend_one(TokenTree::Punct(Punct::new('>', Spacing::Alone)));
+    stream
+}
+
+/// The identifier of a field along with its JSON key,
+/// raw identifiers like `r#type` use the key `type`.
+pub(crate) fn field_ident(field: &Field) -> (Ident, String) {
+    match field.name().strip_prefix("r#") {
+        Some(name) => (Ident::new_raw(name, Span::call_site()), name.to_string()),
+        None => (Ident::new(field.name(), Span::call_site()), field.name().to_string()),
+    }
+}
@@ -0,0 +1,36 @@
+use proc_macro::{Ident, Literal, Span, TokenStream, quote};
+
+use super::{field_ident, impl_generics, respan};
+use crate::struct_def::StructDef;
+
+/// Generates an implementation of `iovera::JsonSerialize`
+/// which writes the struct as an object with one member per field,
+/// straight into the `Serializer` in declaration order.
+pub(crate) fn derive_serialize(def: &StructDef) -> TokenStream {
+    let name = Ident::new(def.name(), Span::call_site());
+    let (impl_gens, ty_gens) = impl_generics(def, quote!(::iovera::JsonSerialize));
+
+    let mut members = TokenStream::new();
+
+    for field in def.fields() {
+        let (ident, key) = field_ident(field);
+        let key = Literal::string(&key);
+
+        members.extend(quote! {
+            ser.key($key)?;
+            ::iovera::JsonSerialize::serialize(&self.$ident, ser)?;
+        });
+    }
+
+    let tokens = quote! {
+        impl $impl_gens ::iovera::JsonSerialize for $name $ty_gens {
+            fn serialize(&self, ser: &mut ::iovera::Serializer<'_>) -> ::std::fmt::Result {
+                ser.begin_object()?;
+                $members
+                ser.end_object()
+            }
+        }
+    };
+
+    respan(tokens, Span::mixed_site())
+}
@@ -0,0 +1,37 @@
+use std::iter::Peekable;
+
+/// Yields the items of `iter` with a clone of `sep` between every two of them.
+pub(crate) struct Separator<I: Iterator> {
+    iter: Peekable<I>,
+    sep: I::Item,
+    pending: bool,
+}
+
+impl<I: Iterator> Separator<I> {
+    pub(crate) fn new(iter: I, sep: I::Item) -> Self {
+        Self {
+            iter: iter.peekable(),
+            sep,
+            pending: false,
+        }
+    }
+}
+
+impl<I: Iterator> Iterator for Separator<I>
+where
+    I::Item: Clone,
+{
+    type Item = I::Item;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.pending {
+            self.pending = false;
+            return Some(self.sep.clone());
+        }
+
+        let item = self.iter.next()?;
+        self.pending = self.iter.peek().is_some();
+
+        Some(item)
+    }
+}
@@ -1,2 +1 @@
 pub(crate) mod interweave;
-pub(crate) mod tuple_iter;
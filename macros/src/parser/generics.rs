@@ -11,18 +11,13 @@ impl Generic {
         Self { traits, name }
     }
 
-    pub fn insert(&mut self, tr: Ident) {
-        match self.traits {
-            Some(ref mut vec) => vec.push(tr),
-            None => {
-                self.traits = Some(Vec::with_capacity(4));
-                self.traits.as_mut().unwrap().push(tr)
-            }
-        }
+    pub fn name(&self) -> &Ident {
+        &self.name
     }
 
-    pub fn to_tokens(self) -> impl IntoIterator<Item = TokenTree> {
-        std::iter::once(TokenTree::Ident(self.name))
+    /// Traits bounding the generic, like `Iterator` in `<I: Iterator>`
+    pub fn traits(&self) -> &[Ident] {
+        self.traits.as_deref().unwrap_or_default()
     }
 
     pub fn as_token(self) -> TokenTree {
@@ -1,16 +1,18 @@
 use proc_macro::{Ident, Punct, Span, TokenTree};
 
-use super::ty::Borrow;
-
 #[derive(Debug)]
 pub struct Lifetime {
     name: String,
-    apostrophe: Span,
 }
 
 impl Lifetime {
-    pub fn new(name: String, apostrophe: Span) -> Lifetime {
-        Self { name, apostrophe }
+    pub fn new(name: String) -> Lifetime {
+        Self { name }
+    }
+
+    /// Name of the lifetime without the `'`
+    pub fn name(&self) -> &str {
+        &self.name
     }
 
     pub fn into_tokens(self) -> LifetimeIter {
@@ -20,14 +22,6 @@ impl Lifetime {
         let iterable = [Some(TokenTree::Punct(mark)), Some(TokenTree::Ident(name))];
         LifetimeIter { iterable, slot: 0 }
     }
-
-    pub fn into_borrow(self) -> Borrow {
-        Borrow::new(false, self)
-    }
-
-    pub fn into_borrow_mut(self) -> Borrow {
-        Borrow::new(true, self)
-    }
 }
 
 pub struct LifetimeIter {
@@ -1,3 +1,5 @@
+#![allow(dead_code)]
+
 /// Identifiers preceeding some definition
 /// like:
 /// ```rust
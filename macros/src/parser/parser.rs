@@ -1,5 +1,5 @@
 #![allow(dead_code)]
-use proc_macro::{Group, Ident, Literal, Punct, Span, TokenStream, TokenTree};
+use proc_macro::{Delimiter, Group, Ident, Literal, Punct, Spacing, Span, TokenStream, TokenTree};
 
 use super::generics::Generic;
 use super::lifetime::Lifetime;
@@ -51,7 +51,7 @@ impl Stream {
         // }
 
         unsafe {
-            let val = (*self.ptr).get_unchecked(self.pos);
+            let val = (&*self.ptr).get_unchecked(self.pos);
             Some(val.clone())
         }
     }
@@ -59,7 +59,7 @@ impl Stream {
     pub fn peek_steps(&mut self, steps: usize) -> Option<TokenTree> {
         if steps == 0 {
             return unsafe {
-                let val = (*self.ptr).get_unchecked(self.pos);
+                let val = (&*self.ptr).get_unchecked(self.pos);
 
                 Some(val.clone())
             };
@@ -70,7 +70,7 @@ impl Stream {
         };
 
         unsafe {
-            let val = (*self.ptr).get_unchecked(self.pos + steps);
+            let val = (&*self.ptr).get_unchecked(self.pos + steps);
             Some(val.clone())
         }
     }
@@ -81,7 +81,7 @@ impl Stream {
         };
 
         unsafe {
-            let val = (*self.ptr).get_unchecked(self.pos);
+            let val = (&*self.ptr).get_unchecked(self.pos);
             self.pos += 1;
 
             Some(val.clone())
@@ -91,7 +91,7 @@ impl Stream {
     pub fn forward_steps(&mut self, steps: usize) -> Option<TokenTree> {
         if steps == 0 {
             return unsafe {
-                let val = (*self.ptr).get_unchecked(self.pos);
+                let val = (&*self.ptr).get_unchecked(self.pos);
 
                 Some(val.clone())
             };
@@ -104,7 +104,7 @@ impl Stream {
         unsafe {
             self.pos += steps;
 
-            let val = (*self.ptr).get_unchecked(self.pos);
+            let val = (&*self.ptr).get_unchecked(self.pos);
 
             Some(val.clone())
         }
@@ -113,7 +113,7 @@ impl Stream {
     pub fn back_steps(&mut self, steps: usize) -> Option<TokenTree> {
         if steps == 0 {
             return unsafe {
-                let val = (*self.ptr).get_unchecked(self.pos);
+                let val = (&*self.ptr).get_unchecked(self.pos);
 
                 Some(val.clone())
             };
@@ -124,7 +124,7 @@ impl Stream {
         unsafe {
             self.pos -= steps;
 
-            let val = (*self.ptr).get_unchecked(self.pos);
+            let val = (&*self.ptr).get_unchecked(self.pos);
 
             Some(val.clone())
         }
@@ -138,7 +138,7 @@ impl Stream {
         }
 
         unsafe {
-            let val = (*self.ptr).get_unchecked(self.pos);
+            let val = (&*self.ptr).get_unchecked(self.pos);
 
             Some(val.clone())
         }
@@ -177,13 +177,15 @@ impl ParserError {
             .collect();
         let group = TokenTree::Group(Group::new(proc_macro::Delimiter::Parenthesis, gr_stream));
 
-        [ident, punct, group].into_iter().collect()
+        // the `;` lets the invocation stand in item position, like in derives
+        let semi = TokenTree::Punct(Punct::new(';', proc_macro::Spacing::Alone));
+
+        [ident, punct, group, semi].into_iter().collect()
     }
 }
 
 macro_rules! parse_error {
     (Eof, $self:ident) => {{
-        dbg!("eof tracker");
         let err = ParserError::Eof("Unexpected EOF", $self.get_last_span());
 
         Err(err)
@@ -203,7 +205,6 @@ macro_rules! parse_error {
 
 macro_rules! distinguish {
     (Ident, $match:ident) => {{
-        dbg!(&$match);
         match $match {
             TokenTree::Ident(id) => id,
             t => return parse_error!(WrongToken, t.span(), "ident"),
@@ -243,7 +244,6 @@ impl<T: std::fmt::Debug> OptVec<T> {
     }
 
     pub(crate) fn push(&mut self, item: T) {
-        dbg!(&item);
         if self.is_ready {
             self.vec
                 .as_mut()
@@ -257,6 +257,7 @@ impl<T: std::fmt::Debug> OptVec<T> {
         container.push(item);
 
         self.vec = Some(container);
+        self.is_ready = true;
     }
 
     pub(crate) fn into_self(mut self) -> Option<Vec<T>> {
@@ -409,13 +410,18 @@ impl Parser {
         let mut struct_attrs: Vec<proc_macro::Ident> = Vec::with_capacity(3);
 
         loop {
-            let ident = self.ident()?;
+            match self.eof_next()? {
+                TokenTree::Ident(ident) if ident.to_string() == "struct" => break,
+                TokenTree::Ident(ident) => struct_attrs.push(ident),
 
-            if ident.to_string() == "struct" {
-                break;
-            }
+                // Attributes like `#[derive(Debug)]`, the group is skipped with the `#`
+                TokenTree::Punct(pc) if pc.as_char() == '#' => self.skip(),
+
+                // Visibility restrictions like `pub(crate)`
+                TokenTree::Group(gr) if gr.delimiter() == Delimiter::Parenthesis => {}
 
-            struct_attrs.push(ident);
+                tkn => return parse_error!(WrongToken, tkn.span(), "`struct`", tkn),
+            }
         }
 
         let struct_name = self.ident()?.to_string();
@@ -427,10 +433,17 @@ impl Parser {
         use TokenTree::*;
         'vabank: loop {
             match self.eof_next()? {
-                Group(gr) => {
+                Group(gr) if gr.delimiter() == Delimiter::Brace => {
+                    // `Stream` can't be empty
+                    if gr.stream().is_empty() {
+                        break 'vabank;
+                    }
+
                     let mut parser = Parser::new(gr.stream());
 
                     loop {
+                        skip_field_prefix(&mut parser);
+
                         if parser.tkns.end() {
                             break;
                         }
@@ -450,14 +463,22 @@ impl Parser {
 
                         match parser.next().expect("this should not be `None`") {
                             Ident(type_name) => {
+                                let type_name = last_path_segment(&mut parser, type_name)?;
+
                                 // Just the type name
-                                let field = match parser.punct()?.as_char() {
-                                    ',' => {
+                                let field = match parser.next() {
+                                    // The last field doesn't need a trailing `,`
+                                    None => {
+                                        let field_type = Ty::new(None, type_name, None, None);
+                                        Field::new(field_type, field_name.to_string())
+                                    }
+
+                                    Some(Punct(pc)) if pc.as_char() == ',' => {
                                         let field_type = Ty::new(None, type_name, None, None);
                                         Field::new(field_type, field_name.to_string())
                                     }
 
-                                    '<' => {
+                                    Some(Punct(pc)) if pc.as_char() == '<' => {
                                         let (gens, lfs) = dig_up_generics_lifetimes(&mut parser)?;
 
                                         let field_type = Ty::new(None, type_name, gens, lfs);
@@ -465,17 +486,17 @@ impl Parser {
                                         // After the `dig_up_generics_lifetimes` function is called
                                         // the cursor will be on the character after `>`
                                         // It should be `,` so we can just skip it
-                                        dbg!(parser.next());
+                                        parser.next();
 
                                         Field::new(field_type, field_name.to_string())
                                     }
 
-                                    ch => {
+                                    Some(tkn) => {
                                         return parse_error!(
                                             WrongToken,
-                                            parser.get_last_span(),
+                                            tkn.span(),
                                             "`<` or `,`",
-                                            ch
+                                            tkn
                                         );
                                     }
                                 };
@@ -504,7 +525,7 @@ impl Parser {
                                     // test it out!
                                     parser.skip();
                                     match eof_match!(parser.next(), parser) {
-                                        Ident(name) => dbg!(name),
+                                        Ident(name) => name,
                                         any => {
                                             return parse_error!(
                                                 WrongToken,
@@ -515,7 +536,7 @@ impl Parser {
                                         }
                                     }
                                 } else {
-                                    dbg!(parser.ident())?
+                                    parser.ident()?
                                 };
 
                                 let field = create_field(
@@ -545,6 +566,10 @@ impl Parser {
                     break 'vabank;
                 }
 
+                Group(gr) => {
+                    return parse_error!(WrongToken, gr.span(), "a struct with named fields");
+                }
+
                 // Here we check if the punct is a `<`
                 // this denotes type lifetimes, generics, etc...
                 Punct(pc) if pc.as_char() == '<' => {
@@ -596,7 +621,7 @@ fn dig_up_generics_lifetimes(
             TokenTree::Punct(pc) if pc.as_char() == '\'' => {
                 let lifetime_name = parser.ident()?;
 
-                let lf = Lifetime::new(lifetime_name.to_string(), pc.span());
+                let lf = Lifetime::new(lifetime_name.to_string());
                 lifetimes.push(lf);
 
                 // for correctness
@@ -619,7 +644,12 @@ fn dig_up_generics_lifetimes(
 
             TokenTree::Punct(pc) if pc.as_char() == '>' => break,
 
+            // Nested generics like the inner `<T>` in `Vec<Box<T>>` aren't kept
+            TokenTree::Punct(pc) if pc.as_char() == '<' => skip_nested_generics(parser)?,
+
             TokenTree::Ident(id) => {
+                let id = last_path_segment(parser, id)?;
+
                 let next_token = match parser.peek() {
                     None => return parse_error!(Eof, parser),
                     Some(tkn) => distinguish!(Punct, tkn),
@@ -647,7 +677,7 @@ fn dig_up_generics_lifetimes(
 
                                 ',' => {
                                     parser.skip();
-                                    //break;
+                                    break;
                                 }
 
                                 '>' => {
@@ -694,18 +724,16 @@ fn create_field(
     // Checks if we have a `<`
     // this can be the start of a sequence of generic types, lifetimes, etc...
     // like `Test<'a, T, I, 'b`.
-    let has_extra_markers = match parser.peek() {
-        None => return parse_error!(Eof, parser),
-        Some(val) => {
-            parser.skip();
-            val.to_string() == "<"
-        }
+    // The last field doesn't need a trailing `,`
+    let has_extra_markers = match parser.next() {
+        None => false,
+        Some(val) => val.to_string() == "<",
     };
 
     let borrow = match lf_name {
         None => None,
         Some(name) => {
-            let lf = Lifetime::new(name.to_string(), name.span());
+            let lf = Lifetime::new(name.to_string());
             let borrow = Borrow::new(is_mutable, lf);
             Some(borrow)
         }
@@ -722,3 +750,65 @@ fn create_field(
 
     Ok(Field::new(ty, field_name.to_string()))
 }
+
+// Skips attributes and the visibility in front of a field
+// like `#[doc = "..."] pub(crate) field: Type`.
+fn skip_field_prefix(parser: &mut Parser) {
+    loop {
+        match parser.peek() {
+            Some(TokenTree::Punct(pc)) if pc.as_char() == '#' => {
+                parser.skip();
+                parser.skip();
+            }
+
+            Some(TokenTree::Ident(id)) if id.to_string() == "pub" => {
+                parser.skip();
+
+                if let Some(TokenTree::Group(gr)) = parser.peek() {
+                    if gr.delimiter() == Delimiter::Parenthesis {
+                        parser.skip();
+                    }
+                }
+            }
+
+            _ => break,
+        }
+    }
+}
+
+// Follows a path like `std::string::String`, returning its last segment.
+fn last_path_segment(parser: &mut Parser, mut ident: Ident) -> Result<Ident> {
+    loop {
+        match parser.peek() {
+            // `::` is lexed as a joint `:` followed by another `:`
+            Some(TokenTree::Punct(pc)) if pc.as_char() == ':' && pc.spacing() == Spacing::Joint => {
+                parser.skip();
+
+                match parser.punct()?.as_char() {
+                    ':' => {}
+                    ch => return parse_error!(WrongToken, parser.get_last_span(), "`::`", ch),
+                }
+
+                ident = parser.ident()?;
+            }
+
+            _ => return Ok(ident),
+        }
+    }
+}
+
+// Requires that the current parser's position be 1 after the nested `<`,
+// leaves it 1 after the matching `>`.
+fn skip_nested_generics(parser: &mut Parser) -> Result<()> {
+    let mut depth = 1;
+
+    while depth != 0 {
+        match parser.eof_next()? {
+            TokenTree::Punct(pc) if pc.as_char() == '<' => depth += 1,
+            TokenTree::Punct(pc) if pc.as_char() == '>' => depth -= 1,
+            _ => {}
+        }
+    }
+
+    Ok(())
+}
@@ -2,7 +2,6 @@ use proc_macro::{Ident, Punct, Spacing, Span, TokenTree};
 
 use super::{generics::Generic, lifetime::Lifetime};
 use crate::iters::interweave::Separator;
-use std::iter;
 
 #[derive(Debug)]
 pub(crate) struct Ty {
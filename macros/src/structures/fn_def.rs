@@ -1,3 +1,5 @@
+#![allow(dead_code)]
+
 use proc_macro::{Group, Ident, Punct, Spacing, Span};
 
 use crate::TokenStream;
@@ -7,7 +9,7 @@ use crate::ty::Ty;
 use std::iter;
 
 #[derive(Debug)]
-struct Arg {
+pub struct Arg {
     name: Ident,
     arg_type: Ty,
 }
@@ -91,7 +91,6 @@ impl<T: std::fmt::Debug> OptVec<T> {
     }
 
     fn push(&mut self, item: T) {
-        dbg!(&item);
         if self.is_ready {
             self.vec
                 .as_mut()
@@ -125,6 +124,15 @@ impl Field {
             field_name,
         }
     }
+
+    /// Name of the field as written, raw identifiers keep their `r#`.
+    pub(crate) fn name(&self) -> &str {
+        &self.field_name
+    }
+
+    pub(crate) fn ty(&self) -> &Ty {
+        &self.field_type
+    }
 }
 
 #[derive(Debug)]
@@ -152,6 +160,23 @@ impl StructDef {
             lifetimes,
         }
     }
+
+    pub(crate) fn name(&self) -> &str {
+        &self.name
+    }
+
+    pub(crate) fn fields(&self) -> &[Field] {
+        &self.fields
+    }
+
+    pub(crate) fn generics(&self) -> &[Generic] {
+        self.generics.as_deref().unwrap_or_default()
+    }
+
+    pub(crate) fn lifetimes(&self) -> &[Lifetime] {
+        self.lifetimes.as_deref().unwrap_or_default()
+    }
+
     // pub(crate) fn analyze_stream(tokens: TokenStream) -> Self {
     //     let mut parser = Parser {
     //         iter: tokens.clone().into_iter().peekable(),
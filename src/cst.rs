@@ -0,0 +1,1069 @@
+/*
+    Lossless concrete syntax tree, for editing documents without disturbing their formatting.
+*/
+
+use std::fmt;
+use std::str::FromStr;
+
+use crate::error::{Error, ErrorKind, SerializeError};
+use crate::lexer::{Dialect, Lexer, Span, Token};
+use crate::map::Map;
+use crate::options::{ParseOptions, SerializeOptions};
+use crate::parser::{check_depth, check_members, ident_key, wrong_token};
+use crate::ser::{to_string, to_string_with};
+use crate::traits::JsonSerialize;
+use crate::value::{JsonValue, Number};
+
+type Result<T> = std::result::Result<T, Error>;
+
+/// A JSON document kept byte for byte as it was written, along with a tree of its values.
+///
+/// Every token of the tree has a `Span` into `Document::text`, the whitespace and comments
+/// between tokens stay in the text where they were. Edits only rewrite the text
+/// of what they touch and move the spans after it, so printing the document gives back
+/// everything else unchanged.
+///
+/// Values are found by JSON Pointer (RFC 6901), like `/servers/0/port`.
+/// Every member of an object is kept, a key written twice is found by its last member.
+#[derive(Debug, Clone)]
+pub struct Document {
+    text: String,
+    root: Node,
+    options: ParseOptions,
+}
+
+/// A value of a `Document` along with where it is written.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Node {
+    span: Span,
+    kind: NodeKind,
+}
+
+/// What a `Node` is, containers holding the nodes of their entries.
+#[derive(Debug, Clone, PartialEq)]
+pub enum NodeKind {
+    /// A string, number, bool or `null`, decoded.
+    Scalar(JsonValue),
+    Object(Vec<Member>),
+    Array(Vec<Element>),
+}
+
+/// A member of an object.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Member {
+    key: String,
+    key_span: Span,
+    colon: Span,
+    value: Node,
+    comma: Option<Span>,
+}
+
+/// An element of an array.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Element {
+    value: Node,
+    comma: Option<Span>,
+}
+
+impl Document {
+    /// Parses `text` as strict JSON with the default `ParseOptions`.
+    pub fn new(text: impl Into<String>) -> Result<Self> {
+        Self::with_options(text, ParseOptions::new())
+    }
+
+    /// Parses `text` accepting the given `Dialect`, comments are kept like any other text.
+    pub fn with_dialect(text: impl Into<String>, dialect: Dialect) -> Result<Self> {
+        Self::with_options(text, ParseOptions::new().dialect(dialect))
+    }
+
+    /// Parses `text` enforcing the limits of `options`, except the policy for duplicate keys
+    /// and sorted maps, which don't apply.
+    pub fn with_options(text: impl Into<String>, options: ParseOptions) -> Result<Self> {
+        let text = text.into();
+        let root = Builder::new(Lexer::with_options(&text, options)).root()?;
+
+        Ok(Self {
+            text,
+            root,
+            options,
+        })
+    }
+
+    /// The text of the document, with the edits made so far.
+    pub fn text(&self) -> &str {
+        &self.text
+    }
+
+    pub fn into_text(self) -> String {
+        self.text
+    }
+
+    pub fn root(&self) -> &Node {
+        &self.root
+    }
+
+    pub fn options(&self) -> &ParseOptions {
+        &self.options
+    }
+
+    /// The node `pointer` leads to.
+    pub fn get(&self, pointer: &str) -> Option<&Node> {
+        self.root.find(&steps(pointer)?)
+    }
+
+    /// The whole document as a `JsonValue`.
+    pub fn to_value(&self) -> JsonValue {
+        self.root.to_value()
+    }
+
+    /// Writes `value` where `pointer` leads, replacing the text of the value there.
+    /// A missing last step is added like `Document::insert` does.
+    /// Returns `false` if there is nowhere to write, or the error of writing `value` out.
+    ///
+    /// # Panics
+    /// If the `JsonSerialize` implementation doesn't write a single value.
+    pub fn set<T: JsonSerialize + ?Sized>(
+        &mut self,
+        pointer: &str,
+        value: &T,
+    ) -> std::result::Result<bool, SerializeError> {
+        let Some(steps) = steps(pointer) else {
+            return Ok(false);
+        };
+
+        let span = match self.root.find(&steps) {
+            Some(node) => node.span,
+            None => return self.insert(pointer, value),
+        };
+
+        let text = to_string_with(value, &SerializeOptions::compact())?;
+        let node = value_node(&text, span.start());
+
+        self.splice(span.start(), span.end(), &text);
+        if let Some(old) = self.root.find_mut(&steps) {
+            *old = node;
+        }
+
+        Ok(true)
+    }
+
+    /// Adds `value` where `pointer` leads, as a new member at the end of an object
+    /// or as an element before the given index of an array, `-` appending it.
+    /// The new entry copies the indentation and spacing of its neighbour.
+    /// Returns `false` if there is nowhere to add or the key is taken,
+    /// or the error of writing `value` out.
+    ///
+    /// # Panics
+    /// If the `JsonSerialize` implementation doesn't write a single value.
+    pub fn insert<T: JsonSerialize + ?Sized>(
+        &mut self,
+        pointer: &str,
+        value: &T,
+    ) -> std::result::Result<bool, SerializeError> {
+        let Some(mut steps) = steps(pointer) else {
+            return Ok(false);
+        };
+        let Some(last) = steps.pop() else {
+            return Ok(false);
+        };
+        let Some(container) = self.root.find(&steps) else {
+            return Ok(false);
+        };
+
+        let text = to_string_with(value, &SerializeOptions::compact())?;
+        let entries = container.entries();
+
+        let (index, key) = match &container.kind {
+            NodeKind::Object(members) if members.iter().all(|member| member.key != last) => {
+                (members.len(), Some(last))
+            }
+
+            NodeKind::Array(elements) => match array_index(&last, elements.len()) {
+                Some(index) if index <= elements.len() => (index, None),
+                _ => return Ok(false),
+            },
+
+            _ => return Ok(false),
+        };
+
+        let insertion = match index < entries.list.len() {
+            true => entries.insert_before(&self.text, index, &text),
+            false => entries.append(&self.text, key.as_deref(), &text),
+        };
+
+        let node = value_node(&text, insertion.value);
+        self.splice(insertion.at, insertion.at, &insertion.text);
+
+        let container = self
+            .root
+            .find_mut(&steps)
+            .expect("the container was found before");
+
+        match (&mut container.kind, key) {
+            (NodeKind::Object(members), Some(key)) => {
+                if let Some(last) = members.last_mut() {
+                    last.comma = last.comma.or(insertion.previous_comma);
+                }
+
+                members.push(Member {
+                    key,
+                    key_span: insertion.key.expect("members are inserted with their key"),
+                    colon: insertion
+                        .colon
+                        .expect("members are inserted with their `:`"),
+                    value: node,
+                    comma: insertion.comma,
+                });
+            }
+
+            (NodeKind::Array(elements), None) => {
+                if let Some(last) = elements.last_mut() {
+                    last.comma = last.comma.or(insertion.previous_comma);
+                }
+
+                let element = Element {
+                    value: node,
+                    comma: insertion.comma,
+                };
+                elements.insert(index, element);
+            }
+
+            _ => unreachable!("entries are inserted into the container they were made for"),
+        }
+
+        Ok(true)
+    }
+
+    /// Removes the member or element `pointer` leads to along with its `,`
+    /// and the whitespace and comments before it, returning its value.
+    pub fn remove(&mut self, pointer: &str) -> Option<JsonValue> {
+        let mut steps = steps(pointer)?;
+        let last = steps.pop()?;
+        let container = self.root.find(&steps)?;
+
+        let index = match &container.kind {
+            NodeKind::Object(members) => members.iter().rposition(|member| member.key == last)?,
+            NodeKind::Array(elements) => array_index(&last, elements.len())?,
+            NodeKind::Scalar(_) => return None,
+        };
+
+        let entries = container.entries().list;
+        let entry = entries.get(index)?;
+        let last_comma = entry.comma.is_none() && index > 0;
+
+        let (start, end) = match (entry.comma, index.checked_sub(1)) {
+            (Some(comma), _) => (entry.lead, comma.end()),
+
+            // the last entry takes the `,` before it along
+            (None, Some(previous)) => match entries[previous].comma {
+                Some(comma) => (comma.start(), entry.end),
+                None => (entry.lead, entry.end),
+            },
+
+            (None, None) => (entry.lead, entry.end),
+        };
+
+        self.splice(start, end, "");
+        let container = self
+            .root
+            .find_mut(&steps)
+            .expect("the container was found before");
+
+        let removed = match &mut container.kind {
+            NodeKind::Object(members) => {
+                let removed = members.remove(index).value;
+                if let Some(previous) = members.last_mut().filter(|_| last_comma) {
+                    previous.comma = None;
+                }
+
+                removed
+            }
+
+            NodeKind::Array(elements) => {
+                let removed = elements.remove(index).value;
+                if let Some(previous) = elements.last_mut().filter(|_| last_comma) {
+                    previous.comma = None;
+                }
+
+                removed
+            }
+
+            NodeKind::Scalar(_) => unreachable!("only containers have entries"),
+        };
+
+        Some(removed.to_value())
+    }
+
+    /// Renames the member `pointer` leads to, leaving its value and position alone.
+    /// Returns `false` if it isn't a member or another member has the new key.
+    pub fn rename(&mut self, pointer: &str, key: &str) -> bool {
+        let Some(mut steps) = steps(pointer) else {
+            return false;
+        };
+        let Some(last) = steps.pop() else {
+            return false;
+        };
+        let Some(NodeKind::Object(members)) = self.root.find(&steps).map(Node::kind) else {
+            return false;
+        };
+        let Some(index) = members.iter().rposition(|member| member.key == last) else {
+            return false;
+        };
+
+        if last == key {
+            return true;
+        }
+
+        if members.iter().any(|member| member.key == key) {
+            return false;
+        }
+
+        let span = members[index].key_span;
+        let text = to_string(key);
+        self.splice(span.start(), span.end(), &text);
+
+        if let Some(NodeKind::Object(members)) =
+            self.root.find_mut(&steps).map(|node| &mut node.kind)
+        {
+            members[index].key = key.to_owned();
+            members[index].key_span = Span::new(span.start(), span.start() + text.len());
+        }
+
+        true
+    }
+
+    /// Replaces `start..end` of the text with `replacement`, like an edit made in an editor,
+    /// and brings the tree up to date. Only the innermost object or array around the edit
+    /// is lexed and parsed again, the nodes around it are kept with their spans moved.
+    /// If the new text of that container doesn't make a single value, the one around it is
+    /// tried, up to the whole document.
+    ///
+    /// Returns the span of the text that was parsed again. On error the document is left
+    /// as it was. Limits of `ParseOptions` on the whole input are only checked
+    /// when the whole document is parsed again.
+    ///
+    /// # Panics
+    /// If `start..end` is out of bounds or splits a character, like `String::replace_range`.
+    pub fn edit(&mut self, start: usize, end: usize, replacement: &str) -> Result<Span> {
+        assert!(
+            start <= end && self.text.is_char_boundary(start) && self.text.is_char_boundary(end),
+            "cannot edit {start}..{end} of a document of length {}",
+            self.text.len()
+        );
+
+        // innermost container first
+        if let Some(path) = self.root.enclosing(start, end) {
+            for depth in (0..=path.len()).rev() {
+                if let Some(span) = self.reparse(&path[..depth], start, end, replacement) {
+                    return Ok(span);
+                }
+            }
+        }
+
+        // nothing short of the whole document will do
+        let mut text = self.text.clone();
+        text.replace_range(start..end, replacement);
+        *self = Self::with_options(text, self.options)?;
+
+        Ok(Span::new(0, self.text.len()))
+    }
+
+    // Applies the edit of `Document::edit` parsing only the text of the container `path`
+    // leads to, giving its new span, or `None` if its new text isn't a single value.
+    fn reparse(
+        &mut self,
+        path: &[usize],
+        start: usize,
+        end: usize,
+        replacement: &str,
+    ) -> Option<Span> {
+        let span = self.root.at(path).span;
+
+        let mut text = String::with_capacity(span.len() + replacement.len());
+        text.push_str(&self.text[span.start()..start]);
+        text.push_str(replacement);
+        text.push_str(&self.text[end..span.end()]);
+
+        // the containers around it count towards the depth limit
+        let builder = Builder {
+            lexer: Lexer::with_options(&text, self.options),
+            depth: path.len(),
+        };
+
+        let mut node = builder.root().ok()?;
+        node.shift(Shift {
+            start: 0,
+            end: 0,
+            len: span.start(),
+        });
+
+        let reparsed = node.span;
+        self.splice(start, end, replacement);
+        *self.root.at_mut(path) = node;
+
+        Some(reparsed)
+    }
+
+    // Replaces `start..end` of the text with `replacement` and moves the spans after it,
+    // the nodes that were in the replaced range are left for the caller to fix.
+    fn splice(&mut self, start: usize, end: usize, replacement: &str) {
+        self.text.replace_range(start..end, replacement);
+        self.root.shift(Shift {
+            start,
+            end,
+            len: replacement.len(),
+        });
+    }
+}
+
+impl fmt::Display for Document {
+    /// Writes the text of the document, as written apart from the edits.
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.text)
+    }
+}
+
+impl FromStr for Document {
+    type Err = Error;
+
+    fn from_str(text: &str) -> Result<Self> {
+        Self::new(text)
+    }
+}
+
+impl Node {
+    /// Where the value is written, from its first byte to its last.
+    pub fn span(&self) -> Span {
+        self.span
+    }
+
+    pub fn kind(&self) -> &NodeKind {
+        &self.kind
+    }
+
+    /// The value along with everything in it, the last of duplicate keys winning.
+    pub fn to_value(&self) -> JsonValue {
+        match &self.kind {
+            NodeKind::Scalar(value) => value.clone(),
+            NodeKind::Object(members) => JsonValue::Object(
+                members
+                    .iter()
+                    .map(|member| (member.key.as_str(), member.value.to_value()))
+                    .collect::<Map>(),
+            ),
+            NodeKind::Array(elements) => JsonValue::Array(
+                elements
+                    .iter()
+                    .map(|element| element.value.to_value())
+                    .collect(),
+            ),
+        }
+    }
+
+    // The node the reference tokens `steps` lead to from this one.
+    fn find(&self, steps: &[String]) -> Option<&Node> {
+        steps.iter().try_fold(self, |node, step| match &node.kind {
+            NodeKind::Object(members) => members
+                .iter()
+                .rev()
+                .find(|member| member.key == *step)
+                .map(|member| &member.value),
+
+            NodeKind::Array(elements) => elements
+                .get(array_index(step, elements.len())?)
+                .map(|element| &element.value),
+
+            NodeKind::Scalar(_) => None,
+        })
+    }
+
+    fn find_mut(&mut self, steps: &[String]) -> Option<&mut Node> {
+        steps
+            .iter()
+            .try_fold(self, |node, step| match &mut node.kind {
+                NodeKind::Object(members) => members
+                    .iter_mut()
+                    .rev()
+                    .find(|member| member.key == *step)
+                    .map(|member| &mut member.value),
+
+                NodeKind::Array(elements) => {
+                    let index = array_index(step, elements.len())?;
+                    elements.get_mut(index).map(|element| &mut element.value)
+                }
+
+                NodeKind::Scalar(_) => None,
+            })
+    }
+
+    // Positions of the entries leading from this node to the innermost container
+    // whose brackets enclose `start..end`, `None` if this node isn't one.
+    fn enclosing(&self, start: usize, end: usize) -> Option<Vec<usize>> {
+        let mut path = Vec::new();
+        let mut node = self;
+
+        if !node.encloses(start, end) {
+            return None;
+        }
+
+        loop {
+            // entries are in the order of the text, only the first one ending after `start` can do
+            let index = match &node.kind {
+                NodeKind::Scalar(_) => break,
+                NodeKind::Object(members) => {
+                    members.partition_point(|member| member.value.span.end() <= start)
+                }
+                NodeKind::Array(elements) => {
+                    elements.partition_point(|element| element.value.span.end() <= start)
+                }
+            };
+
+            match node.entry(index) {
+                Some(child) if child.encloses(start, end) => {
+                    path.push(index);
+                    node = child;
+                }
+
+                _ => break,
+            }
+        }
+
+        Some(path)
+    }
+
+    // Whether this is a container and `start..end` lies between its brackets.
+    fn encloses(&self, start: usize, end: usize) -> bool {
+        !matches!(self.kind, NodeKind::Scalar(_))
+            && start > self.span.start()
+            && end < self.span.end()
+    }
+
+    // The value of the entry at `index` of a container.
+    fn entry(&self, index: usize) -> Option<&Node> {
+        match &self.kind {
+            NodeKind::Scalar(_) => None,
+            NodeKind::Object(members) => members.get(index).map(|member| &member.value),
+            NodeKind::Array(elements) => elements.get(index).map(|element| &element.value),
+        }
+    }
+
+    // The node the positions of entries `path` lead to, which has to lead somewhere.
+    fn at(&self, path: &[usize]) -> &Node {
+        path.iter().fold(self, |node, &index| {
+            node.entry(index).expect("the path was found before")
+        })
+    }
+
+    fn at_mut(&mut self, path: &[usize]) -> &mut Node {
+        path.iter().fold(self, |node, &index| match &mut node.kind {
+            NodeKind::Scalar(_) => unreachable!("paths only go through containers"),
+            NodeKind::Object(members) => &mut members[index].value,
+            NodeKind::Array(elements) => &mut elements[index].value,
+        })
+    }
+
+    // Moves every span of the node after `shift`, see `Shift`.
+    fn shift(&mut self, shift: Shift) {
+        // nothing before the edit moves
+        if self.span.end() <= shift.start {
+            return;
+        }
+
+        self.span = shift.span(self.span);
+
+        match &mut self.kind {
+            NodeKind::Scalar(_) => {}
+
+            NodeKind::Object(members) => {
+                for member in members {
+                    member.key_span = shift.span(member.key_span);
+                    member.colon = shift.span(member.colon);
+                    member.value.shift(shift);
+                    member.comma = member.comma.map(|comma| shift.span(comma));
+                }
+            }
+
+            NodeKind::Array(elements) => {
+                for element in elements {
+                    element.value.shift(shift);
+                    element.comma = element.comma.map(|comma| shift.span(comma));
+                }
+            }
+        }
+    }
+
+    // Extents of the entries of a container, none for scalars.
+    fn entries(&self) -> Entries {
+        let mut entries = Entries {
+            open: self.span.start(),
+            list: Vec::new(),
+        };
+
+        let mut push = |key: Option<(Span, Span)>, value: &Node, comma: Option<Span>| {
+            let lead = match entries.list.last() {
+                Some(&Entry {
+                    comma: Some(comma), ..
+                }) => comma.end(),
+                _ => entries.open + 1,
+            };
+
+            entries.list.push(Entry {
+                lead,
+                start: key.map_or(value.span.start(), |(key, _)| key.start()),
+                key,
+                value: value.span.start(),
+                end: value.span.end(),
+                comma,
+            });
+        };
+
+        match &self.kind {
+            NodeKind::Scalar(_) => {}
+            NodeKind::Object(members) => members.iter().for_each(|member| {
+                push(
+                    Some((member.key_span, member.colon)),
+                    &member.value,
+                    member.comma,
+                )
+            }),
+            NodeKind::Array(elements) => elements
+                .iter()
+                .for_each(|element| push(None, &element.value, element.comma)),
+        }
+
+        entries
+    }
+}
+
+impl Member {
+    /// The key, decoded.
+    pub fn key(&self) -> &str {
+        &self.key
+    }
+
+    /// The key as written, quotes included.
+    pub fn key_span(&self) -> Span {
+        self.key_span
+    }
+
+    pub fn colon(&self) -> Span {
+        self.colon
+    }
+
+    pub fn value(&self) -> &Node {
+        &self.value
+    }
+
+    /// The `,` after the value, missing for the last member unless it has a trailing comma.
+    pub fn comma(&self) -> Option<Span> {
+        self.comma
+    }
+}
+
+impl Element {
+    pub fn value(&self) -> &Node {
+        &self.value
+    }
+
+    /// The `,` after the value, missing for the last element unless it has a trailing comma.
+    pub fn comma(&self) -> Option<Span> {
+        self.comma
+    }
+}
+
+// Builds the nodes of a `Document` from the tokens of its `Lexer`.
+struct Builder<'b> {
+    lexer: Lexer<'b>,
+
+    // containers currently open
+    depth: usize,
+}
+
+impl<'b> Builder<'b> {
+    fn new(lexer: Lexer<'b>) -> Self {
+        Self { lexer, depth: 0 }
+    }
+
+    fn root(mut self) -> Result<Node> {
+        let node = self.node()?;
+
+        match self.lexer.next().transpose()? {
+            None => Ok(node),
+            Some(token) => wrong_token!(self, token, "end of input after the root value"),
+        }
+    }
+
+    fn error(&self, kind: ErrorKind, span: Span) -> Error {
+        self.lexer.error(kind, span)
+    }
+
+    fn text(&self, span: Span) -> &'b str {
+        span.slice(self.lexer.input()).unwrap_or_default()
+    }
+
+    // The next token, running out of tokens is an error.
+    fn next(&mut self, expected: &'static str) -> Result<Token<'b>> {
+        match self.lexer.next().transpose()? {
+            Some(token) => Ok(token),
+            None => {
+                let end = self.lexer.input().len();
+                Err(self.error(ErrorKind::UnexpectedEof(expected), Span::new(end, end)))
+            }
+        }
+    }
+
+    fn node(&mut self) -> Result<Node> {
+        let (kind, span) = match self.next("a value")? {
+            Token::LeftBracket(open) => {
+                self.enter(open)?;
+                let (members, close) = self.object()?;
+                (NodeKind::Object(members), open.join(close))
+            }
+
+            Token::LeftSquareBracket(open) => {
+                self.enter(open)?;
+                let (elements, close) = self.array()?;
+                (NodeKind::Array(elements), open.join(close))
+            }
+
+            Token::String(string, span) => (JsonValue::String(string.into_owned()).into(), span),
+            Token::Int(num, span) => (JsonValue::Number(Number::Int(num)).into(), span),
+            Token::Float(num, span) => (JsonValue::Number(Number::Float(num)).into(), span),
+            Token::Bool(val, span) => (JsonValue::Bool(val).into(), span),
+            Token::Null(span) => (JsonValue::Null.into(), span),
+
+            token => return wrong_token!(self, token, "a value"),
+        };
+
+        Ok(Node { span, kind })
+    }
+
+    // Requires the `{` to be already consumed, gives the span of the `}` along with the members.
+    fn object(&mut self) -> Result<(Vec<Member>, Span)> {
+        let mut members: Vec<Member> = Vec::new();
+
+        let close = loop {
+            let (key, key_span) = match self.next("a string key")? {
+                // right after the `{` or a trailing comma
+                Token::RightBracket(close)
+                    if members.is_empty() || self.lexer.dialect().trailing_commas() =>
+                {
+                    break close;
+                }
+
+                Token::String(key, span) => (key.into_owned(), span),
+                token => match ident_key(self.lexer.dialect(), &token, self.text(*token.span())) {
+                    Some(key) => (key.into_owned(), *token.span()),
+                    None => return wrong_token!(self, token, "a string key"),
+                },
+            };
+
+            let max = self.lexer.options().max_key_len;
+            if key.len() > max {
+                return Err(self.error(ErrorKind::KeyTooLong(max), key_span));
+            }
+
+            check_members(self.lexer.options(), members.len())
+                .map_err(|kind| self.error(kind, key_span))?;
+
+            let colon = match self.next("`:`")? {
+                Token::Colon(span) => span,
+                token => return wrong_token!(self, token, "`:`"),
+            };
+
+            let mut member = Member {
+                key,
+                key_span,
+                colon,
+                value: self.node()?,
+                comma: None,
+            };
+
+            match self.next("`,` or `}`")? {
+                Token::Comma(span) => member.comma = Some(span),
+                Token::RightBracket(close) => {
+                    members.push(member);
+                    break close;
+                }
+                token => return wrong_token!(self, token, "`,` or `}`"),
+            }
+
+            members.push(member);
+        };
+
+        self.depth -= 1;
+        Ok((members, close))
+    }
+
+    // Requires the `[` to be already consumed, gives the span of the `]` along with the elements.
+    fn array(&mut self) -> Result<(Vec<Element>, Span)> {
+        let mut elements: Vec<Element> = Vec::new();
+
+        let close = loop {
+            if let Some(&Token::RightSquareBracket(close)) = self.lexer.peek(0)?
+                && (elements.is_empty() || self.lexer.dialect().trailing_commas())
+            {
+                self.lexer.next();
+                break close;
+            }
+
+            let span = match self.lexer.peek(0)? {
+                Some(token) => *token.span(),
+                None => Span::new(self.lexer.input().len(), self.lexer.input().len()),
+            };
+            check_members(self.lexer.options(), elements.len())
+                .map_err(|kind| self.error(kind, span))?;
+
+            let mut element = Element {
+                value: self.node()?,
+                comma: None,
+            };
+
+            match self.next("`,` or `]`")? {
+                Token::Comma(span) => element.comma = Some(span),
+                Token::RightSquareBracket(close) => {
+                    elements.push(element);
+                    break close;
+                }
+                token => return wrong_token!(self, token, "`,` or `]`"),
+            }
+
+            elements.push(element);
+        };
+
+        self.depth -= 1;
+        Ok((elements, close))
+    }
+
+    // Opens the container whose bracket is at `span`, minding the depth limit.
+    fn enter(&mut self, span: Span) -> Result<()> {
+        check_depth(self.lexer.options(), self.depth).map_err(|kind| self.error(kind, span))?;
+
+        self.depth += 1;
+        Ok(())
+    }
+}
+
+impl From<JsonValue> for NodeKind {
+    fn from(value: JsonValue) -> Self {
+        Self::Scalar(value)
+    }
+}
+
+// An edit replacing `start..end` of the text with `len` bytes.
+// Whatever starts after the replaced range moves with the end of it,
+// whatever ends inside or after it, containers around the edit included, moves its end.
+#[derive(Debug, Clone, Copy)]
+struct Shift {
+    start: usize,
+    end: usize,
+    len: usize,
+}
+
+impl Shift {
+    fn span(self, span: Span) -> Span {
+        let start = match span.start() >= self.end {
+            true => self.offset(span.start()),
+            false => span.start(),
+        };
+
+        let end = match span.end() > self.start && span.end() >= self.end {
+            true => self.offset(span.end()),
+            false => span.end(),
+        };
+
+        Span::new(start, end)
+    }
+
+    // Where `offset`, at or after the replaced range, ends up.
+    fn offset(self, offset: usize) -> usize {
+        offset - self.end + self.start + self.len
+    }
+}
+
+// Extents of the entries of a container.
+#[derive(Debug)]
+struct Entries {
+    // offset of the opening bracket
+    open: usize,
+    list: Vec<Entry>,
+}
+
+// Extent of one entry, the spans of its key and `:` given for members.
+#[derive(Debug)]
+struct Entry {
+    // end of the `,` or bracket before it, the whitespace and comments in between are its own
+    lead: usize,
+    start: usize,
+    key: Option<(Span, Span)>,
+    value: usize,
+    end: usize,
+    comma: Option<Span>,
+}
+
+// Text inserted for a new entry, and where its pieces land once it is in.
+#[derive(Debug, Default)]
+struct Insertion {
+    at: usize,
+    text: String,
+    key: Option<Span>,
+    colon: Option<Span>,
+    value: usize,
+    comma: Option<Span>,
+
+    // the `,` the entry before gets, if it had none
+    previous_comma: Option<Span>,
+}
+
+impl Insertion {
+    // Adds `piece` to the text, returning its span once inserted.
+    fn push(&mut self, piece: &str) -> Span {
+        let start = self.at + self.text.len();
+        self.text.push_str(piece);
+
+        Span::new(start, start + piece.len())
+    }
+}
+
+impl Entries {
+    // Adds an entry after the last one, `key` given for members,
+    // copying the indentation of the last entry and the spacing around its `:`.
+    fn append(&self, text: &str, key: Option<&str>, value: &str) -> Insertion {
+        let mut insertion = Insertion::default();
+
+        let (lead, before_colon, after_colon) = match self.list.last() {
+            // right after the opening bracket of an empty container
+            None => {
+                insertion.at = self.open + 1;
+                ("", "", " ")
+            }
+
+            Some(last) => {
+                match last.comma {
+                    Some(comma) => insertion.at = comma.end(),
+                    None => {
+                        insertion.at = last.end;
+                        insertion.previous_comma = Some(insertion.push(","));
+                    }
+                }
+
+                let (before, after) = match last.key {
+                    Some((key, colon)) => (
+                        &text[key.end()..colon.start()],
+                        &text[colon.end()..last.value],
+                    ),
+                    None => ("", ""),
+                };
+
+                (
+                    indentation(&text[last.lead..last.start]),
+                    indentation(before),
+                    indentation(after),
+                )
+            }
+        };
+
+        insertion.push(lead);
+        if let Some(key) = key {
+            insertion.key = Some(insertion.push(&to_string(key)));
+            insertion.push(before_colon);
+            insertion.colon = Some(insertion.push(":"));
+            insertion.push(after_colon);
+        }
+
+        insertion.value = insertion.push(value).start();
+
+        // a trailing comma stays trailing
+        if self.list.last().is_some_and(|last| last.comma.is_some()) {
+            insertion.comma = Some(insertion.push(","));
+        }
+
+        insertion
+    }
+
+    // Adds an element before the one at `index`, which keeps its indentation
+    // while the new one takes its place.
+    fn insert_before(&self, text: &str, index: usize, value: &str) -> Insertion {
+        let entry = &self.list[index];
+        let mut insertion = Insertion {
+            at: entry.start,
+            ..Insertion::default()
+        };
+
+        insertion.value = insertion.push(value).start();
+        insertion.comma = Some(insertion.push(","));
+
+        // the trivia right after an opening bracket is no separator, the next entry
+        // has one, a lone entry keeps its own unless it has none
+        let lead = |entry: &Entry| indentation(&text[entry.lead..entry.start]);
+        let separator = match (index, self.list.get(1)) {
+            (0, Some(next)) => lead(next),
+            (0, None) if lead(entry).is_empty() => " ",
+            _ => lead(entry),
+        };
+        insertion.push(separator);
+
+        insertion
+    }
+}
+
+// --- HELPER FUNCTIONS --- //
+
+// The reference tokens of a JSON Pointer, `None` unless it is empty or starts with a `/`.
+fn steps(pointer: &str) -> Option<Vec<String>> {
+    if pointer.is_empty() {
+        return Some(Vec::new());
+    }
+
+    let steps = pointer
+        .strip_prefix('/')?
+        .split('/')
+        .map(|step| step.replace("~1", "/").replace("~0", "~"))
+        .collect();
+
+    Some(steps)
+}
+
+// The array index a reference token stands for, `-` standing for `len`,
+// the position after the last element.
+fn array_index(step: &str, len: usize) -> Option<usize> {
+    match step {
+        "-" => Some(len),
+
+        // no sign and no leading zeros
+        _ if step.starts_with('0') && step.len() > 1 => None,
+        _ if !step.bytes().all(|byte| byte.is_ascii_digit()) => None,
+        _ => step.parse().ok(),
+    }
+}
+
+// The indentation `trivia` ends with, the line break before it included,
+// or without a line break the whitespace it ends with, so comments aren't copied along.
+fn indentation(trivia: &str) -> &str {
+    let Some(newline) = trivia.rfind('\n') else {
+        return &trivia[trivia.trim_end().len()..];
+    };
+
+    let start = match trivia[..newline].ends_with('\r') {
+        true => newline - 1,
+        false => newline,
+    };
+
+    let line = &trivia[newline + 1..];
+    &trivia[start..trivia.len() - line.trim_start().len()]
+}
+
+// The node of `text`, a value written by the `Serializer`, once inserted at `at`.
+fn value_node(text: &str, at: usize) -> Node {
+    let mut node = Builder::new(Lexer::with_options(text, ParseOptions::unlimited()))
+        .root()
+        .expect("a `JsonSerialize` implementation wrote something other than a single value");
+
+    node.shift(Shift {
+        start: 0,
+        end: 0,
+        len: at,
+    });
+
+    node
+}
@@ -0,0 +1,310 @@
+/*
+    Errors of the lexer, parser and deserializers, and of the serializer.
+*/
+
+use std::fmt;
+
+use crate::lexer::Span;
+use crate::line_index::{Encoding, LineCol, LineIndex};
+
+/// What went wrong, see `Error`.
+#[derive(Debug, Clone, PartialEq)]
+pub enum ErrorKind {
+    /// The input ended while something was still expected,
+    /// carries a description of what.
+    UnexpectedEof(&'static str),
+
+    /// A character that can't start any token.
+    UnexpectedCharacter(char),
+
+    /// A number that breaks the grammar, carries the reason.
+    InvalidNumber(&'static str),
+
+    /// A malformed escape in a string, carries the reason.
+    InvalidEscape(&'static str),
+
+    /// A `\uXXXX` escape of a surrogate that isn't part of a pair.
+    LoneSurrogate,
+
+    /// A control character written in a string without escaping it.
+    ControlCharacter(char),
+
+    /// A bare word other than `true`, `false` or `null`.
+    InvalidLiteral,
+
+    /// A token that has no place in the grammar where it was found.
+    UnexpectedToken {
+        found: &'static str,
+        expected: &'static str,
+    },
+
+    /// A number that doesn't fit the type it is read into.
+    OutOfRange(&'static str),
+
+    /// A field a `JsonDeserialize` type requires is not in the object.
+    MissingField(&'static str),
+
+    /// An object has a key the `JsonDeserialize` type doesn't know.
+    UnknownField(String),
+
+    /// Input read from an `io::Read` that isn't valid UTF-8.
+    InvalidUtf8,
+
+    /// Reading the input failed, carries the message of the `io::Error`.
+    Io(String),
+
+    /// More containers open at once than `ParseOptions::max_depth`, which it carries.
+    DepthLimitExceeded(usize),
+
+    /// A string longer than `ParseOptions::max_string_len`, which it carries.
+    StringTooLong(usize),
+
+    /// A key longer than `ParseOptions::max_key_len`, which it carries.
+    KeyTooLong(usize),
+
+    /// More tokens than `ParseOptions::max_tokens`, which it carries.
+    TooManyTokens(usize),
+
+    /// A container with more entries than `ParseOptions::max_members`, which it carries.
+    TooManyMembers(usize),
+
+    /// Input longer than `ParseOptions::max_input_len`, which it carries.
+    InputTooLarge(usize),
+
+    /// A key written twice in the same object under `DuplicateKeys::Error`,
+    /// the error is at the second one and `first` is the span of the first one.
+    DuplicateKey { key: String, first: Span },
+}
+
+impl ErrorKind {
+    /// Whether this is going over one of the limits of `ParseOptions`.
+    pub fn is_limit(&self) -> bool {
+        matches!(
+            self,
+            Self::DepthLimitExceeded(_)
+                | Self::StringTooLong(_)
+                | Self::KeyTooLong(_)
+                | Self::TooManyTokens(_)
+                | Self::TooManyMembers(_)
+                | Self::InputTooLarge(_)
+        )
+    }
+}
+
+impl fmt::Display for ErrorKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::UnexpectedEof(expected) => {
+                write!(f, "unexpected end of input, expected {expected}")
+            }
+            Self::UnexpectedCharacter(ch) => write!(f, "unexpected character {ch:?}"),
+            Self::InvalidNumber(reason) => write!(f, "invalid number, {reason}"),
+            Self::InvalidEscape(reason) => write!(f, "invalid escape, {reason}"),
+            Self::LoneSurrogate => write!(f, "unpaired surrogate in a unicode escape"),
+            Self::ControlCharacter(ch) => {
+                write!(
+                    f,
+                    "unescaped control character {:#04x} in a string",
+                    *ch as u32
+                )
+            }
+            Self::InvalidLiteral => {
+                write!(f, "invalid literal, expected `true`, `false` or `null`")
+            }
+            Self::UnexpectedToken { found, expected } => {
+                write!(f, "expected {expected}, found {found}")
+            }
+            Self::OutOfRange(ty) => write!(f, "number out of range for `{ty}`"),
+            Self::MissingField(name) => write!(f, "missing field `{name}`"),
+            Self::UnknownField(name) => write!(f, "unknown field `{name}`"),
+            Self::InvalidUtf8 => write!(f, "invalid UTF-8"),
+            Self::Io(msg) => write!(f, "failed to read the input: {msg}"),
+            Self::DepthLimitExceeded(max) => write!(f, "nesting deeper than {max} levels"),
+            Self::StringTooLong(max) => write!(f, "string longer than {max} bytes"),
+            Self::KeyTooLong(max) => write!(f, "key longer than {max} bytes"),
+            Self::TooManyTokens(max) => write!(f, "more than {max} tokens"),
+            Self::TooManyMembers(max) => write!(f, "container with more than {max} entries"),
+            Self::InputTooLarge(max) => write!(f, "input larger than {max} bytes"),
+            Self::DuplicateKey { key, first } => {
+                let (start, end) = first.as_tuple();
+                write!(
+                    f,
+                    "duplicate key {key:?}, first written at bytes {start}..{end}"
+                )
+            }
+        }
+    }
+}
+
+/// An error along with where in the input it happened.
+///
+/// `Display` gives a one line message,
+/// `Error::snippet` renders the offending line of the source with the span underlined.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Error {
+    kind: ErrorKind,
+    span: Span,
+    line: usize,
+    column: usize,
+}
+
+impl Error {
+    /// Creates an error at `span` of `source`, working out its line and column.
+    pub fn new(kind: ErrorKind, span: Span, source: &str) -> Self {
+        Self::indexed(kind, span, &LineIndex::new(source))
+    }
+
+    /// Creates an error at `span` of the source `index` was built over,
+    /// for many errors in the same source.
+    pub(crate) fn indexed(kind: ErrorKind, span: Span, index: &LineIndex) -> Self {
+        let LineCol { line, column } = index.position(span.start(), Encoding::Chars);
+
+        Self {
+            kind,
+            span,
+            line: line + 1,
+            column: column + 1,
+        }
+    }
+
+    /// Creates an error whose line and column are already known.
+    pub(crate) fn at(kind: ErrorKind, span: Span, line: usize, column: usize) -> Self {
+        Self {
+            kind,
+            span,
+            line,
+            column,
+        }
+    }
+
+    /// Moves an error found in a slice of the input into the whole input,
+    /// the slice starting at byte `offset`, on `line` and `column`.
+    pub(crate) fn offset_by(self, offset: usize, line: usize, column: usize) -> Self {
+        let column = match self.line {
+            1 => column + self.column - 1,
+            _ => self.column,
+        };
+
+        Self::at(
+            self.kind,
+            self.span.shifted(offset),
+            line + self.line - 1,
+            column,
+        )
+    }
+
+    pub fn kind(&self) -> &ErrorKind {
+        &self.kind
+    }
+
+    /// Byte span of the offending input.
+    pub fn span(&self) -> Span {
+        self.span
+    }
+
+    /// Line of the start of the span, starting at 1.
+    pub fn line(&self) -> usize {
+        self.line
+    }
+
+    /// Column of the start of the span in characters, starting at 1.
+    pub fn column(&self) -> usize {
+        self.column
+    }
+
+    /// Renders the error along with the line of `source` it points into,
+    /// `source` has to be the input the error came from.
+    pub fn snippet<'e>(&'e self, source: &'e str) -> Snippet<'e> {
+        Snippet {
+            error: self,
+            source,
+        }
+    }
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{} at line {}, column {}",
+            self.kind, self.line, self.column
+        )
+    }
+}
+
+impl std::error::Error for Error {}
+
+/// Why serializing failed.
+#[derive(Debug, Clone, PartialEq)]
+pub enum SerializeError {
+    /// A NaN or infinite float, canonical output has no room for them.
+    NonFiniteFloat(f64),
+
+    /// An integer canonical output can't write as a double without rounding it.
+    InexactInteger(i128),
+
+    /// The sink or a `JsonSerialize` implementation returned an error.
+    Write,
+}
+
+impl fmt::Display for SerializeError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::NonFiniteFloat(num) => write!(f, "{num} has no canonical JSON form"),
+            Self::InexactInteger(num) => write!(f, "{num} can't be written exactly as a double"),
+            Self::Write => write!(f, "writing the JSON out failed"),
+        }
+    }
+}
+
+impl std::error::Error for SerializeError {}
+
+/// An `Error` rendered with its source line, like:
+///
+/// ```text
+/// error: expected a value, found `}`
+///  --> line 2, column 8
+///   |
+/// 2 | "key": }
+///   |        ^
+/// ```
+pub struct Snippet<'e> {
+    error: &'e Error,
+    source: &'e str,
+}
+
+impl fmt::Display for Snippet<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let Error {
+            kind,
+            span,
+            line,
+            column,
+        } = self.error;
+
+        let text = self.source.lines().nth(line - 1).unwrap_or_default();
+        let gutter = line.to_string().len();
+
+        // the underline stops at the end of the line
+        let (start, end) = span.as_tuple();
+        let rest = text.chars().count().saturating_sub(column - 1);
+        let width = self
+            .source
+            .get(start..end)
+            .map_or(1, |spanned| spanned.chars().count())
+            .clamp(1, rest.max(1));
+
+        writeln!(f, "error: {kind}")?;
+        writeln!(f, "{:gutter$}--> line {line}, column {column}", "")?;
+        writeln!(f, "{:gutter$} |", "")?;
+        writeln!(f, "{line} | {text}")?;
+        write!(
+            f,
+            "{:gutter$} | {:pad$}{}",
+            "",
+            "",
+            "^".repeat(width),
+            pad = column - 1
+        )
+    }
+}
@@ -0,0 +1,400 @@
+/*
+    Events of the streaming parsers and the grammar checking their order.
+*/
+
+use std::borrow::Cow;
+
+use crate::error::{Error, ErrorKind};
+use crate::lexer::{Dialect, Lexer, Span, Token};
+use crate::options::ParseOptions;
+use crate::parser::{check_depth, check_members, describe, ident_key};
+use crate::value::Number;
+
+type Result<T> = std::result::Result<T, Error>;
+
+/// One step through a JSON document, in the order of the input.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Event<'e> {
+    StartObject,
+    EndObject,
+    StartArray,
+    EndArray,
+
+    /// The key of the next member of an object, its value comes next.
+    Key(Cow<'e, str>),
+
+    String(Cow<'e, str>),
+    Number(Number),
+    Bool(bool),
+    Null,
+}
+
+impl Event<'_> {
+    /// Copies any text borrowed from the input so the event can outlive it.
+    pub fn into_owned(self) -> Event<'static> {
+        match self {
+            Self::StartObject => Event::StartObject,
+            Self::EndObject => Event::EndObject,
+            Self::StartArray => Event::StartArray,
+            Self::EndArray => Event::EndArray,
+            Self::Key(key) => Event::Key(Cow::Owned(key.into_owned())),
+            Self::String(string) => Event::String(Cow::Owned(string.into_owned())),
+            Self::Number(num) => Event::Number(num),
+            Self::Bool(val) => Event::Bool(val),
+            Self::Null => Event::Null,
+        }
+    }
+}
+
+/// An `Event` along with where it is in the input.
+#[derive(Debug, Clone, PartialEq)]
+pub struct SpannedEvent<'e> {
+    pub event: Event<'e>,
+
+    /// Span of the token the event comes from, a bracket for the container events.
+    pub span: Span,
+
+    /// Number of containers around the event,
+    /// the brackets of the root container are at depth 0 and its members at 1.
+    pub depth: usize,
+}
+
+impl SpannedEvent<'_> {
+    pub fn into_owned(self) -> SpannedEvent<'static> {
+        SpannedEvent {
+            event: self.event.into_owned(),
+            span: self.span,
+            depth: self.depth,
+        }
+    }
+}
+
+/// Pulls `SpannedEvent`s out of a `Lexer` without building a tree.
+///
+/// The grammar is enforced on the way, keys are strings, members and elements
+/// are separated by commas and brackets match, so consumers can trust the order of events.
+/// Stops after the first error.
+#[derive(Debug)]
+pub struct Events<'e> {
+    lexer: Lexer<'e>,
+    grammar: Grammar,
+    done: bool,
+}
+
+impl<'e> Events<'e> {
+    /// Creates a new `Events` over `input` accepting strict JSON.
+    pub fn new(input: &'e str) -> Self {
+        Self::from_lexer(Lexer::new(input))
+    }
+
+    /// Creates a new `Events` over `input` accepting the given `Dialect`.
+    pub fn with_dialect(input: &'e str, dialect: Dialect) -> Self {
+        Self::from_lexer(Lexer::with_dialect(input, dialect))
+    }
+
+    /// Creates a new `Events` over `input` enforcing the limits of `options`.
+    pub fn with_options(input: &'e str, options: ParseOptions) -> Self {
+        Self::from_lexer(Lexer::with_options(input, options))
+    }
+
+    /// Creates a new `Events` over the tokens of an existing `Lexer`, using its options.
+    pub fn from_lexer(lexer: Lexer<'e>) -> Self {
+        Self {
+            grammar: Grammar::new(*lexer.options()),
+            lexer,
+            done: false,
+        }
+    }
+
+    /// Number of containers open after the last event.
+    pub fn depth(&self) -> usize {
+        self.grammar.depth()
+    }
+
+    fn next_event(&mut self) -> Result<Option<SpannedEvent<'e>>> {
+        let input = self.lexer.input();
+
+        while let Some(token) = self.lexer.next().transpose()? {
+            let span = *token.span();
+            let text = span.slice(input).unwrap_or_default();
+
+            match self.grammar.token(token, text) {
+                Ok(Some(event)) => return Ok(Some(event)),
+                Ok(None) => continue,
+                Err(kind) => return Err(self.lexer.error(kind, span)),
+            }
+        }
+
+        match self.grammar.finish() {
+            Ok(()) => Ok(None),
+            Err(kind) => Err(self.lexer.error(kind, Span::new(input.len(), input.len()))),
+        }
+    }
+}
+
+impl<'e> Iterator for Events<'e> {
+    type Item = Result<SpannedEvent<'e>>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.done {
+            return None;
+        }
+
+        let result = self.next_event();
+
+        if !matches!(result, Ok(Some(_))) {
+            self.done = true;
+        }
+
+        result.transpose()
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum State {
+    // a value, at the root or after a `:`
+    Value,
+
+    // right after `[`, a value or `]`
+    FirstElement,
+
+    // after a `,` in an array
+    Element,
+
+    // right after `{`, a key or `}`
+    FirstKey,
+
+    // after a `,` in an object
+    Key,
+
+    // after a key
+    Colon,
+
+    // after a value inside a container, `,` or the closing bracket
+    CommaOrEnd,
+
+    // the root value is complete
+    Done,
+}
+
+// the span of the offending token is up to the caller
+type GrammarResult<T> = std::result::Result<T, ErrorKind>;
+
+/// Checks tokens fed one at a time against the grammar and the depth,
+/// member and key limits, turning them into `Event`s.
+/// Keeps no more state than the open containers.
+#[derive(Debug)]
+pub(crate) struct Grammar {
+    options: ParseOptions,
+    state: State,
+
+    // containers currently open, `true` for objects
+    stack: Vec<bool>,
+
+    // entries read so far of each open container
+    entries: Vec<usize>,
+}
+
+impl Grammar {
+    pub(crate) fn new(options: ParseOptions) -> Self {
+        Self {
+            options,
+            state: State::Value,
+            stack: Vec::new(),
+            entries: Vec::new(),
+        }
+    }
+
+    /// Number of containers currently open.
+    pub(crate) fn depth(&self) -> usize {
+        self.stack.len()
+    }
+
+    /// Takes the next token, `text` being its text in the input.
+    /// Separators give no event.
+    pub(crate) fn token<'e>(
+        &mut self,
+        token: Token<'e>,
+        text: &str,
+    ) -> GrammarResult<Option<SpannedEvent<'e>>> {
+        let span = *token.span();
+        let event = self.event(token, text)?;
+
+        // the brackets of a container are outside of it
+        let depth = match event {
+            Some(Event::StartObject | Event::StartArray) => self.depth() - 1,
+            _ => self.depth(),
+        };
+
+        Ok(event.map(|event| SpannedEvent { event, span, depth }))
+    }
+
+    fn event<'e>(&mut self, token: Token<'e>, text: &str) -> GrammarResult<Option<Event<'e>>> {
+        let trailing = self.options.dialect.trailing_commas();
+
+        match self.state {
+            State::Value => self.value(token),
+
+            State::FirstElement | State::Element => match token {
+                Token::RightSquareBracket(_) if self.state == State::FirstElement || trailing => {
+                    Ok(Some(self.close()))
+                }
+
+                token => {
+                    self.count_entry()?;
+                    self.value(token)
+                }
+            },
+
+            State::FirstKey | State::Key => match token {
+                Token::RightBracket(_) if self.state == State::FirstKey || trailing => {
+                    Ok(Some(self.close()))
+                }
+
+                Token::String(key, _) => self.key(key),
+
+                token => match ident_key(self.options.dialect, &token, text) {
+                    Some(key) => self.key(key),
+                    None => self.unexpected(&token),
+                },
+            },
+
+            State::Colon => match token {
+                Token::Colon(_) => {
+                    self.state = State::Value;
+                    Ok(None)
+                }
+
+                token => self.unexpected(&token),
+            },
+
+            State::CommaOrEnd => match (token, self.stack.last()) {
+                (Token::Comma(_), Some(true)) => {
+                    self.state = State::Key;
+                    Ok(None)
+                }
+
+                (Token::Comma(_), _) => {
+                    self.state = State::Element;
+                    Ok(None)
+                }
+
+                (Token::RightBracket(_), Some(true))
+                | (Token::RightSquareBracket(_), Some(false)) => Ok(Some(self.close())),
+
+                (token, _) => self.unexpected(&token),
+            },
+
+            State::Done => self.unexpected(&token),
+        }
+    }
+
+    /// Checks the input may end here.
+    pub(crate) fn finish(&self) -> GrammarResult<()> {
+        match self.state {
+            State::Done => Ok(()),
+            _ => Err(ErrorKind::UnexpectedEof(self.expected())),
+        }
+    }
+
+    fn key<'e>(&mut self, key: Cow<'e, str>) -> GrammarResult<Option<Event<'e>>> {
+        let max = self.options.max_key_len;
+        if key.len() > max {
+            return Err(ErrorKind::KeyTooLong(max));
+        }
+
+        self.count_entry()?;
+        self.state = State::Colon;
+
+        Ok(Some(Event::Key(key)))
+    }
+
+    // Counts one more entry of the innermost container.
+    fn count_entry(&mut self) -> GrammarResult<()> {
+        if let Some(count) = self.entries.last_mut() {
+            check_members(&self.options, *count)?;
+            *count += 1;
+        }
+
+        Ok(())
+    }
+
+    // Opens a container, minding the depth limit.
+    fn open(&mut self, object: bool) -> GrammarResult<()> {
+        check_depth(&self.options, self.stack.len())?;
+
+        self.stack.push(object);
+        self.entries.push(0);
+
+        Ok(())
+    }
+
+    fn value<'e>(&mut self, token: Token<'e>) -> GrammarResult<Option<Event<'e>>> {
+        let event = match token {
+            Token::LeftBracket(_) => {
+                self.open(true)?;
+                self.state = State::FirstKey;
+
+                return Ok(Some(Event::StartObject));
+            }
+
+            Token::LeftSquareBracket(_) => {
+                self.open(false)?;
+                self.state = State::FirstElement;
+
+                return Ok(Some(Event::StartArray));
+            }
+
+            Token::String(string, _) => Event::String(string),
+            Token::Int(num, _) => Event::Number(Number::Int(num)),
+            Token::Float(num, _) => Event::Number(Number::Float(num)),
+            Token::Bool(val, _) => Event::Bool(val),
+            Token::Null(_) => Event::Null,
+
+            token => return self.unexpected(&token),
+        };
+
+        self.state = self.after_value();
+        Ok(Some(event))
+    }
+
+    // Pops the innermost container, whose closing bracket was just read.
+    fn close<'e>(&mut self) -> Event<'e> {
+        let object = self.stack.pop().expect("a container is open");
+        self.entries.pop();
+        self.state = self.after_value();
+
+        match object {
+            true => Event::EndObject,
+            false => Event::EndArray,
+        }
+    }
+
+    fn after_value(&self) -> State {
+        match self.stack.is_empty() {
+            true => State::Done,
+            false => State::CommaOrEnd,
+        }
+    }
+
+    fn unexpected<T>(&self, token: &Token) -> GrammarResult<T> {
+        Err(ErrorKind::UnexpectedToken {
+            found: describe(token),
+            expected: self.expected(),
+        })
+    }
+
+    // What the grammar allows next, for error messages.
+    fn expected(&self) -> &'static str {
+        match self.state {
+            State::Value | State::Element => "a value",
+            State::FirstElement => "a value or `]`",
+            State::FirstKey => "a string key or `}`",
+            State::Key => "a string key",
+            State::Colon => "`:`",
+            State::CommaOrEnd if self.stack.last() == Some(&true) => "`,` or `}`",
+            State::CommaOrEnd => "`,` or `]`",
+            State::Done => "end of input after the root value",
+        }
+    }
+}
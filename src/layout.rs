@@ -0,0 +1,297 @@
+/*
+    Width-aware layout of the `Serializer`, keeping objects and arrays on one line when they fit.
+*/
+
+use std::fmt::{self, Write};
+
+use crate::options::SerializeOptions;
+use crate::ser::write_escaped;
+
+// A value written in the smart layout, held back until the root value is complete
+// since whether a container fits depends on everything in it.
+#[derive(Debug)]
+pub(crate) enum Piece {
+    Scalar(String),
+
+    Container {
+        object: bool,
+
+        // keys are given for the members of objects
+        entries: Vec<(Option<String>, Piece)>,
+
+        // the whole container on one line, if the options allow it
+        flat: Option<String>,
+
+        // levels of containers, this one included
+        depth: usize,
+    },
+}
+
+impl Piece {
+    fn flat(&self) -> Option<&str> {
+        match self {
+            Self::Scalar(text) => Some(text),
+            Self::Container { flat, .. } => flat.as_deref(),
+        }
+    }
+
+    fn depth(&self) -> usize {
+        match self {
+            Self::Scalar(_) => 0,
+            Self::Container { depth, .. } => *depth,
+        }
+    }
+}
+
+// The containers open in the smart layout, innermost last.
+#[derive(Debug, Default)]
+pub(crate) struct Layout {
+    open: Vec<Open>,
+}
+
+#[derive(Debug)]
+struct Open {
+    object: bool,
+
+    // the key of the member being written
+    key: Option<String>,
+    entries: Vec<(Option<String>, Piece)>,
+}
+
+impl Layout {
+    /// Whether a container is open, a value written otherwise is the whole root value.
+    pub(crate) fn is_open(&self) -> bool {
+        !self.open.is_empty()
+    }
+
+    pub(crate) fn open(&mut self, object: bool) {
+        self.open.push(Open {
+            object,
+            key: None,
+            entries: Vec::new(),
+        });
+    }
+
+    pub(crate) fn key(&mut self, key: &str) {
+        if let Some(open) = self.open.last_mut() {
+            open.key = Some(key.to_owned());
+        }
+    }
+
+    /// Adds a value to the innermost container, which has to be open.
+    pub(crate) fn push(&mut self, piece: Piece) {
+        if let Some(open) = self.open.last_mut() {
+            let key = open.key.take();
+            open.entries.push((key, piece));
+        }
+    }
+
+    /// Closes the innermost container, giving it back if it was the root value.
+    pub(crate) fn close(&mut self, options: &SerializeOptions) -> Option<Piece> {
+        let Open {
+            object,
+            mut entries,
+            ..
+        } = self.open.pop()?;
+
+        if options.sort_keys {
+            entries.sort_by(|(a, _), (b, _)| a.cmp(b));
+        }
+
+        let depth = 1 + entries
+            .iter()
+            .map(|(_, piece)| piece.depth())
+            .max()
+            .unwrap_or(0);
+
+        // empty containers always fit
+        let flat = match entries.is_empty() || depth <= options.max_inline_depth {
+            true => flat(object, &entries, options),
+            false => None,
+        };
+
+        let piece = Piece::Container {
+            object,
+            entries,
+            flat,
+            depth,
+        };
+
+        match self.is_open() {
+            true => {
+                self.push(piece);
+                None
+            }
+
+            false => Some(piece),
+        }
+    }
+}
+
+/// Writes `piece` as the root value, each container on one line if it fits within
+/// `SerializeOptions::max_width` or over as many as it takes otherwise.
+pub(crate) fn render(
+    out: &mut dyn Write,
+    piece: &Piece,
+    options: &SerializeOptions,
+) -> fmt::Result {
+    Renderer {
+        out,
+        options,
+        indent: options.indent.as_deref().unwrap_or_default(),
+        max_width: options.max_width.unwrap_or(usize::MAX),
+    }
+    .piece(piece, 0, 0, 0)
+}
+
+struct Renderer<'r> {
+    out: &'r mut dyn Write,
+    options: &'r SerializeOptions,
+    indent: &'r str,
+    max_width: usize,
+}
+
+impl Renderer<'_> {
+    // Writes `piece` nested `level` deep, `used` columns of its line taken before it
+    // and `after` more needed after it.
+    fn piece(&mut self, piece: &Piece, level: usize, used: usize, after: usize) -> fmt::Result {
+        let Piece::Container {
+            object, entries, ..
+        } = piece
+        else {
+            return self.out.write_str(piece.flat().unwrap_or_default());
+        };
+
+        // empty containers go on one line however little room is left
+        if let Some(flat) = piece.flat()
+            && (entries.is_empty() || used + width(flat) + after <= self.max_width)
+        {
+            return self.out.write_str(flat);
+        }
+
+        let scalars = entries.iter().all(|(_, piece)| piece.depth() == 0);
+        self.out.write_char(if *object { '{' } else { '[' })?;
+
+        match !object && scalars && self.options.fill_scalar_arrays {
+            true => self.fill(entries, level + 1)?,
+            false => {
+                for (index, (key, entry)) in entries.iter().enumerate() {
+                    let last = index + 1 == entries.len();
+                    let mut used = self.newline(level + 1)?;
+
+                    if let Some(key) = key {
+                        used += self.key(key)?;
+                    }
+
+                    self.piece(entry, level + 1, used, usize::from(!last))?;
+                    if !last {
+                        self.out.write_char(',')?;
+                    }
+                }
+            }
+        }
+
+        self.newline(level)?;
+        self.out.write_char(if *object { '}' } else { ']' })
+    }
+
+    // Writes the scalars of an array as many to a line as fit.
+    fn fill(&mut self, entries: &[(Option<String>, Piece)], level: usize) -> fmt::Result {
+        let start = self.newline(level)?;
+        let mut used = start;
+
+        for (index, (_, entry)) in entries.iter().enumerate() {
+            let text = entry.flat().unwrap_or_default();
+            let last = index + 1 == entries.len();
+            let needed = width(text) + usize::from(!last);
+
+            // the first scalar of a line goes there even if too wide
+            if used > start {
+                match used + 1 + needed <= self.max_width {
+                    true => {
+                        self.out.write_char(' ')?;
+                        used += 1;
+                    }
+                    false => used = self.newline(level)?,
+                }
+            }
+
+            self.out.write_str(text)?;
+            if !last {
+                self.out.write_char(',')?;
+            }
+
+            used += needed;
+        }
+
+        Ok(())
+    }
+
+    // Writes a key and its `:`, giving how many columns they take.
+    fn key(&mut self, key: &str) -> std::result::Result<usize, fmt::Error> {
+        let mut text = String::new();
+        write_escaped(&mut text, key, self.options)?;
+        text.push_str(colon(self.options));
+
+        self.out.write_str(&text)?;
+        Ok(width(&text))
+    }
+
+    // Starts a line indented `level` deep, giving how many columns the indentation takes.
+    fn newline(&mut self, level: usize) -> std::result::Result<usize, fmt::Error> {
+        self.out.write_str(&self.options.newline)?;
+        (0..level).try_for_each(|_| self.out.write_str(self.indent))?;
+
+        Ok(width(self.indent) * level)
+    }
+}
+
+// --- HELPER FUNCTIONS --- //
+
+// A container on one line, if everything in it can be and it fits within
+// `SerializeOptions::max_width`, building stops as soon as the text gets too long.
+fn flat(
+    object: bool,
+    entries: &[(Option<String>, Piece)],
+    options: &SerializeOptions,
+) -> Option<String> {
+    let max_width = options.max_width.unwrap_or(usize::MAX);
+    let mut text = String::from(if object { "{" } else { "[" });
+    let mut used = 1;
+
+    for (index, (key, piece)) in entries.iter().enumerate() {
+        let start = text.len();
+
+        if index > 0 {
+            text.push_str(", ");
+        }
+
+        if let Some(key) = key {
+            write_escaped(&mut text, key, options).ok()?;
+            text.push_str(colon(options));
+        }
+
+        text.push_str(piece.flat()?);
+
+        // the closing bracket needs a column too
+        used += width(&text[start..]);
+        if used + 1 > max_width {
+            return None;
+        }
+    }
+
+    text.push(if object { '}' } else { ']' });
+    Some(text)
+}
+
+fn colon(options: &SerializeOptions) -> &'static str {
+    match options.space_after_colon {
+        true => ": ",
+        false => ":",
+    }
+}
+
+// Columns `text` takes, one per character.
+fn width(text: &str) -> usize {
+    text.chars().count()
+}
@@ -2,11 +2,20 @@
     Lexer for JSON!
 */
 
-type Result<T> = std::result::Result<T, String>;
+use std::borrow::Cow;
+use std::cell::OnceCell;
+use std::collections::VecDeque;
+
+use crate::error::{Error, ErrorKind};
+use crate::line_index::LineIndex;
+use crate::options::ParseOptions;
+use crate::structural::Structurals;
+
+type Result<T> = std::result::Result<T, Error>;
 type Tokens<'ts> = Vec<Token<'ts>>;
 
 #[derive(Debug)]
-pub(crate) enum Token<'t> {
+pub enum Token<'t> {
     LeftBracket(Span),
     RightBracket(Span),
 
@@ -16,70 +25,361 @@ pub(crate) enum Token<'t> {
     Colon(Span),
     Comma(Span),
 
-    String(&'t str, Span),
+    /// Borrowed from the input unless it had escapes.
+    String(Cow<'t, str>, Span),
     Int(i64, Span),
     Float(f64, Span),
     Bool(bool, Span),
 
     Null(Span),
+
+    /// An unquoted JSON5 key, only lexed in `Dialect::Json5`.
+    Ident(Cow<'t, str>, Span),
 }
 
 impl<'t> Token<'t> {
-    fn span(&self) -> &Span {
+    pub fn span(&self) -> &Span {
         match self {
-            &Self::LeftBracket(ref sp) => sp,
-            &Self::RightBracket(ref sp) => sp,
-            &Self::LeftSquareBracket(ref sp) => sp,
-            &Self::RightSquareBracket(ref sp) => sp,
-            &Self::Null(ref sp) => sp,
-            &Self::Colon(ref sp) => sp,
-            &Self::Comma(ref sp) => sp,
+            Self::LeftBracket(sp)
+            | Self::RightBracket(sp)
+            | Self::LeftSquareBracket(sp)
+            | Self::RightSquareBracket(sp)
+            | Self::Null(sp)
+            | Self::Colon(sp)
+            | Self::Comma(sp)
+            | Self::String(_, sp)
+            | Self::Int(_, sp)
+            | Self::Float(_, sp)
+            | Self::Bool(_, sp)
+            | Self::Ident(_, sp) => sp,
+        }
+    }
+
+    /// Copies any text borrowed from the input so the token can outlive it.
+    pub fn into_owned(self) -> Token<'static> {
+        match self {
+            Self::LeftBracket(span) => Token::LeftBracket(span),
+            Self::RightBracket(span) => Token::RightBracket(span),
+            Self::LeftSquareBracket(span) => Token::LeftSquareBracket(span),
+            Self::RightSquareBracket(span) => Token::RightSquareBracket(span),
+            Self::Colon(span) => Token::Colon(span),
+            Self::Comma(span) => Token::Comma(span),
+
+            Self::String(string, span) => Token::String(Cow::Owned(string.into_owned()), span),
+            Self::Int(num, span) => Token::Int(num, span),
+            Self::Float(num, span) => Token::Float(num, span),
+            Self::Bool(val, span) => Token::Bool(val, span),
+            Self::Null(span) => Token::Null(span),
+            Self::Ident(name, span) => Token::Ident(Cow::Owned(name.into_owned()), span),
+        }
+    }
 
-            &Self::String(_, ref sp) => sp,
-            &Self::Int(_, ref sp) => sp,
-            &Self::Float(_, ref sp) => sp,
-            &Self::Bool(_, ref sp) => sp,
+    /// Moves the token `by` bytes further into the input.
+    pub(crate) fn shifted(mut self, by: usize) -> Self {
+        match &mut self {
+            Self::LeftBracket(span)
+            | Self::RightBracket(span)
+            | Self::LeftSquareBracket(span)
+            | Self::RightSquareBracket(span)
+            | Self::Colon(span)
+            | Self::Comma(span)
+            | Self::Null(span)
+            | Self::String(_, span)
+            | Self::Int(_, span)
+            | Self::Float(_, span)
+            | Self::Bool(_, span)
+            | Self::Ident(_, span) => *span = span.shifted(by),
         }
+
+        self
     }
 }
 
-#[derive(Debug)]
-pub(crate) struct Span {
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Span {
     start: usize,
     end: usize,
 }
 
 impl Span {
-    fn new(start: usize, end: usize) -> Self {
+    pub(crate) fn new(start: usize, end: usize) -> Self {
         Span { start, end }
     }
 
-    pub(crate) fn as_tuple(&self) -> (usize, usize) {
+    pub fn as_tuple(&self) -> (usize, usize) {
         (self.start, self.end)
     }
+
+    /// The same span `by` bytes further into the input.
+    pub(crate) fn shifted(&self, by: usize) -> Span {
+        Span::new(self.start + by, self.end + by)
+    }
+
+    /// Byte offset of the first byte of the span.
+    pub fn start(&self) -> usize {
+        self.start
+    }
+
+    /// Byte offset one past the last byte of the span.
+    pub fn end(&self) -> usize {
+        self.end
+    }
+
+    /// Length in bytes.
+    pub fn len(&self) -> usize {
+        self.end - self.start
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.start == self.end
+    }
+
+    /// Smallest span covering both `self` and `other`.
+    pub fn join(&self, other: Span) -> Span {
+        Span::new(self.start.min(other.start), self.end.max(other.end))
+    }
+
+    /// The text of `source` the span covers,
+    /// `None` if it is out of bounds or splits a character.
+    pub fn slice<'s>(&self, source: &'s str) -> Option<&'s str> {
+        source.get(self.start..self.end)
+    }
 }
 
+/// Which flavour of JSON the `Lexer` and `Parser` accept.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Dialect {
+    /// RFC 8259 and nothing more.
+    #[default]
+    Strict,
+
+    /// JSON with `//` and `/* */` comments and trailing commas.
+    Jsonc,
+
+    /// JSON5, which on top of JSONC has single quoted and multi-line strings,
+    /// more escapes, unquoted identifier keys, hex numbers, `Infinity`, `NaN`,
+    /// a leading `+` and leading or trailing decimal points.
+    Json5,
+}
+
+impl Dialect {
+    pub(crate) fn comments(self) -> bool {
+        self != Self::Strict
+    }
+
+    pub(crate) fn trailing_commas(self) -> bool {
+        self != Self::Strict
+    }
+
+    pub(crate) fn is_whitespace(self, ch: char) -> bool {
+        match self {
+            Self::Strict | Self::Jsonc => is_whitespace(ch),
+            Self::Json5 => ch.is_whitespace() || ch == '\u{FEFF}',
+        }
+    }
+}
+
+// Creates an `Err` of the given kind spanning `start..end` of the input,
+// `Lexer::lex_token` works out its line and column.
+macro_rules! lex_error {
+    ($kind:expr, $start:expr, $end:expr) => {
+        Err(Error::at($kind, Span::new($start, $end), 0, 0))
+    };
+}
+
+/// Lexes the input on demand, a token is only produced once asked for.
+///
+/// Only the tokens looked at with `peek` are buffered,
+/// so memory use doesn't grow with the size of the input.
 #[derive(Debug)]
-pub(crate) struct Lexer<'l> {
-    vec: Tokens<'l>,
+pub struct Lexer<'l> {
+    input: &'l str,
+    stream: CharStream<'l>,
+
+    // where the tokens of `Dialect::Strict` input start, to jump over what's in between
+    structurals: Option<Structurals<'l>>,
+
+    // tokens already lexed by `peek`, front is the next one
+    lookahead: VecDeque<Token<'l>>,
+
+    // set once the input ran out or an error was returned
+    done: bool,
+
+    // an error `peek` ran into past the lookahead, `next` returns it once the lookahead is drained
+    error: Option<Error>,
+
+    // lines of the input, only built once there is an error to place
+    line_index: OnceCell<LineIndex<'l>>,
+
+    options: ParseOptions,
+
+    // tokens lexed so far, for `ParseOptions::max_tokens`
     count: usize,
 }
 
 impl<'l> Lexer<'l> {
-    pub fn new(input: &'l str) -> Result<Self> {
-        let mut tokens = Vec::with_capacity(2048);
+    /// Creates a new `Lexer` over `input` with the default `ParseOptions`.
+    pub fn new(input: &'l str) -> Self {
+        Self::with_options(input, ParseOptions::new())
+    }
 
-        let mut stream = input.char_indices().peekable();
+    /// Creates a new `Lexer` over `input` accepting the given `Dialect`.
+    pub fn with_dialect(input: &'l str, dialect: Dialect) -> Self {
+        Self::with_options(input, ParseOptions::new().dialect(dialect))
+    }
 
-        match input.chars().nth(0) {
-            None => return Err("empty".to_string()),
-            Some(ch) if ch != '{' => return Err(format!("invalid character at start: {ch}")),
-            _ => {}
+    /// Creates a new `Lexer` over `input`, enforcing the input, token and string limits of `options`.
+    pub fn with_options(input: &'l str, options: ParseOptions) -> Self {
+        Self {
+            input,
+            stream: CharStream::new(input),
+            structurals: (options.dialect == Dialect::Strict).then(|| Structurals::new(input)),
+            lookahead: VecDeque::new(),
+            done: false,
+            error: None,
+            line_index: OnceCell::new(),
+            options,
+            count: 0,
+        }
+    }
+
+    pub fn dialect(&self) -> Dialect {
+        self.options.dialect
+    }
+
+    pub fn options(&self) -> &ParseOptions {
+        &self.options
+    }
+
+    /// The whole input being lexed.
+    pub(crate) fn input(&self) -> &'l str {
+        self.input
+    }
+
+    /// Creates an error at `span` of the input, every error sharing one `LineIndex`.
+    pub(crate) fn error(&self, kind: ErrorKind, span: Span) -> Error {
+        let index = self.line_index.get_or_init(|| LineIndex::new(self.input));
+        Error::indexed(kind, span, index)
+    }
+
+    /// Lexes the whole input up front.
+    pub fn tokenize(input: &'l str) -> Result<Tokens<'l>> {
+        Self::new(input).collect()
+    }
+
+    /// Looks `peek` tokens ahead without consuming anything,
+    /// `peek(0)` is the token the next call to `next` will return.
+    /// Only as many tokens as needed are lexed and kept around.
+    pub fn peek(&mut self, peek: usize) -> Result<Option<&Token<'l>>> {
+        while self.lookahead.len() <= peek {
+            if let Some(ref err) = self.error {
+                return Err(err.clone());
+            }
+
+            match self.lex_token() {
+                Ok(None) => return Ok(None),
+                Ok(Some(token)) => self.lookahead.push_back(token),
+
+                Err(err) => {
+                    self.error = Some(err.clone());
+                    return Err(err);
+                }
+            }
+        }
+
+        Ok(self.lookahead.get(peek))
+    }
+
+    /// Picks lexing up again after `err`, from the end of its span,
+    /// or for errors inside a string from after its closing quote or at the end of its line.
+    /// Jumps between structural positions are off from then on, the index may be out of step.
+    pub(crate) fn recover(&mut self, err: &Error) {
+        let span = err.span();
+
+        match err.kind() {
+            ErrorKind::InvalidEscape(_)
+            | ErrorKind::LoneSurrogate
+            | ErrorKind::ControlCharacter(_) => {
+                self.stream.seek(span.start());
+                skip_string_rest(&mut self.stream, self.options.dialect);
+            }
+
+            _ => self.stream.seek(span.end()),
+        }
+
+        self.structurals = None;
+        self.lookahead.clear();
+        self.done = false;
+        self.error = None;
+    }
+
+    // Lexes the next token straight from the input.
+    fn lex_token(&mut self) -> Result<Option<Token<'l>>> {
+        if self.done {
+            return Ok(None);
+        }
+
+        let result = self
+            .lex_token_inner()
+            .map_err(|err| self.error(err.kind().clone(), err.span()));
+
+        if !matches!(result, Ok(Some(_))) {
+            self.done = true;
         }
 
+        result
+    }
+
+    fn lex_token_inner(&mut self) -> Result<Option<Token<'l>>> {
+        let input = self.input;
+        let ParseOptions {
+            dialect,
+            max_string_len,
+            max_tokens,
+            max_input_len,
+            ..
+        } = self.options;
+
+        if input.len() > max_input_len {
+            let kind = ErrorKind::InputTooLarge(max_input_len);
+            return lex_error!(kind, max_input_len, input.len());
+        }
+
+        let token = match self.lex_token_unchecked(dialect, max_string_len)? {
+            None => return Ok(None),
+            Some(token) => token,
+        };
+
+        self.count += 1;
+        if self.count > max_tokens {
+            let (start, end) = token.span().as_tuple();
+            return lex_error!(ErrorKind::TooManyTokens(max_tokens), start, end);
+        }
+
+        Ok(Some(token))
+    }
+
+    fn lex_token_unchecked(
+        &mut self,
+        dialect: Dialect,
+        max_string_len: usize,
+    ) -> Result<Option<Token<'l>>> {
+        let input = self.input;
+
         loop {
-            let (index, ch) = match stream.next() {
-                None => break,
+            // whitespace is jumped over in one go when the token starts are known
+            if let Some(ref mut structurals) = self.structurals
+                && let Some(&(index, ch)) = self.stream.peek()
+                && is_whitespace(ch)
+            {
+                match structurals.next_from(index) {
+                    None => return Ok(None),
+                    Some(start) => self.stream.seek(start),
+                }
+            }
+
+            let (index, ch) = match self.stream.next() {
+                None => return Ok(None),
 
                 Some(ch) => ch,
             };
@@ -92,199 +392,679 @@ impl<'l> Lexer<'l> {
                 ':' => Token::Colon(Span::new(index, index + 1)),
                 ',' => Token::Comma(Span::new(index, index + 1)),
 
-                '"' => {
-                    // Handling of strings
+                '"' => match self.quick_string(index, max_string_len) {
+                    Some(token) => token,
+                    None => {
+                        let (string, span) =
+                            lex_string(input, index, &mut self.stream, dialect, max_string_len)?;
 
-                    // loop till we find the ending quote
-                    let start = index;
-                    let mut end = index;
-                    loop {
-                        let next = match stream.next() {
-                            None => {
-                                return Err(format!("unexpected eof at char on pos: {end}"));
-                            } // unexpected EOF.
+                        Token::String(string, span)
+                    }
+                },
 
-                            Some((_, c)) => c,
-                        };
+                '\'' if dialect == Dialect::Json5 => {
+                    let (string, span) =
+                        lex_string(input, index, &mut self.stream, dialect, max_string_len)?;
 
-                        end += 1;
+                    Token::String(string, span)
+                }
+
+                '/' if dialect.comments() => {
+                    skip_comment(input, index, &mut self.stream)?;
+                    continue;
+                }
+
+                // comments need `Dialect::Jsonc` or `Dialect::Json5`
+                '/' => {
+                    return lex_error!(ErrorKind::UnexpectedCharacter('/'), index, index + 1);
+                }
+
+                ch if ch.is_ascii_digit() || ch == '-' => {
+                    lex_number(input, index, &mut self.stream, dialect)?
+                }
 
-                        if next == '"' {
-                            break;
-                        }
+                '+' | '.' if dialect == Dialect::Json5 => {
+                    lex_number(input, index, &mut self.stream, dialect)?
+                }
+
+                '+' => {
+                    return lex_error!(
+                        ErrorKind::InvalidNumber("numbers can't start with a `+`"),
+                        index,
+                        index + 1
+                    );
+                }
+
+                '.' => {
+                    return lex_error!(
+                        ErrorKind::InvalidNumber("a digit has to come before the `.`"),
+                        index,
+                        index + 1
+                    );
+                }
+
+                ch if dialect == Dialect::Json5 && is_ident_start(ch) => {
+                    lex_ident(input, index, &mut self.stream)
+                }
+
+                ch if ch.is_ascii_alphabetic() => match ch {
+                    't' => Token::Bool(true, lex_keyword(input, index, &mut self.stream, "true")?),
+                    'f' => {
+                        Token::Bool(false, lex_keyword(input, index, &mut self.stream, "false")?)
                     }
+                    'n' => Token::Null(lex_keyword(input, index, &mut self.stream, "null")?),
 
-                    Token::String(&input[start + 1..end], Span::new(index, end))
+                    _ => {
+                        // In the name of diagnostics the whole word is spanned
+                        let len = word_len(&input[index..]);
+                        return lex_error!(ErrorKind::InvalidLiteral, index, index + len);
+                    }
+                },
+
+                ch if dialect.is_whitespace(ch) => continue,
+
+                // `@`, `\`, a form feed, any other non-ASCII character and so on
+                ch => {
+                    return lex_error!(
+                        ErrorKind::UnexpectedCharacter(ch),
+                        index,
+                        index + ch.len_utf8()
+                    );
                 }
+            };
+
+            return Ok(Some(token));
+        }
+    }
+
+    // Borrows the string whose opening quote is at `start` straight from the input
+    // if its closing quote is known and there is nothing to decode or check in between.
+    fn quick_string(&mut self, start: usize, max_len: usize) -> Option<Token<'l>> {
+        let end = self.structurals.as_mut()?.next_from(start + 1)?;
+        let string = &self.input[start + 1..end];
+
+        // escapes and control characters are left to `lex_string`, as is reporting long strings
+        let plain =
+            string.len() <= max_len && !string.bytes().any(|byte| byte == b'\\' || byte < 0x20);
+
+        match plain {
+            true => {
+                self.stream.seek(end + 1);
+                Some(Token::String(
+                    Cow::Borrowed(string),
+                    Span::new(start, end + 1),
+                ))
+            }
+
+            false => None,
+        }
+    }
+}
+
+impl<'l> Iterator for Lexer<'l> {
+    type Item = Result<Token<'l>>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if let Some(token) = self.lookahead.pop_front() {
+            return Some(Ok(token));
+        }
+
+        match self.error.take() {
+            Some(err) => Some(Err(err)),
+            None => self.lex_token().transpose(),
+        }
+    }
+}
 
-                ch if ch.is_ascii_digit() => {
-                    let start = index;
-                    let mut end = index;
+// The characters of the input along with their offsets like `Peekable<CharIndices>`,
+// but able to jump to any character boundary.
+#[derive(Debug, Clone)]
+struct CharStream<'l> {
+    input: &'l str,
 
-                    let mut searching_for_float = false;
+    // the character `next` gives, decoded ahead of time so `peek` can hand out a reference
+    peeked: Option<(usize, char)>,
+}
+
+impl<'l> CharStream<'l> {
+    fn new(input: &'l str) -> Self {
+        Self {
+            input,
+            peeked: decode(input, 0),
+        }
+    }
+
+    fn peek(&self) -> Option<&(usize, char)> {
+        self.peeked.as_ref()
+    }
+
+    fn next_if(&mut self, func: impl FnOnce(&(usize, char)) -> bool) -> Option<(usize, char)> {
+        match self.peeked {
+            Some(ref next) if func(next) => self.next(),
+            _ => None,
+        }
+    }
 
-                    loop {
-                        let next = match stream.next() {
-                            None => return Err(format!("unexpected eof at char on pos: {index}")), // unexpected EOF.
+    // Continues from `offset`, which has to be a character boundary.
+    fn seek(&mut self, offset: usize) {
+        self.peeked = decode(self.input, offset);
+    }
+}
 
-                            Some((_, c)) => c,
-                        };
+impl Iterator for CharStream<'_> {
+    type Item = (usize, char);
 
-                        end += 1;
+    fn next(&mut self) -> Option<Self::Item> {
+        let (index, ch) = self.peeked?;
+        self.peeked = decode(self.input, index + ch.len_utf8());
 
-                        match next {
-                            '.' => searching_for_float = true,
-                            ch if ch.is_ascii_digit() => {}
+        Some((index, ch))
+    }
+}
 
-                            ',' => {
-                                break;
-                            }
+// --- HELPER FUNCTIONS --- //
+
+// The character at `offset` of `input` along with the offset.
+fn decode(input: &str, offset: usize) -> Option<(usize, char)> {
+    let ch = input.get(offset..)?.chars().next()?;
+    Some((offset, ch))
+}
+
+// Whitespace as RFC 8259 defines it, which unlike `char::is_ascii_whitespace`
+// doesn't include the form feed.
+fn is_whitespace(ch: char) -> bool {
+    matches!(ch, ' ' | '\t' | '\n' | '\r')
+}
 
-                            _ => {
-                                return Err(format!("invalid char passed as digit at span: {end}"));
-                            } // invalid char
-                        }
+// Characters a JSON5 identifier can start with.
+fn is_ident_start(ch: char) -> bool {
+    ch == '$' || ch == '_' || ch.is_alphabetic()
+}
+
+// Characters that can follow the first one of a JSON5 identifier.
+fn is_ident_part(ch: char) -> bool {
+    is_ident_start(ch) || ch.is_alphanumeric() || matches!(ch, '\u{200C}' | '\u{200D}')
+}
+
+// Lexes a JSON5 identifier whose first character at `start` is already consumed,
+// the names of literals give the literal's token.
+fn lex_ident<'l>(input: &'l str, start: usize, stream: &mut CharStream<'l>) -> Token<'l> {
+    let mut end = input.len();
+    while stream.next_if(|&(_, ch)| is_ident_part(ch)).is_some() {}
+
+    if let Some(&(index, _)) = stream.peek() {
+        end = index;
+    }
+
+    let span = Span::new(start, end);
+    match &input[start..end] {
+        "true" => Token::Bool(true, span),
+        "false" => Token::Bool(false, span),
+        "null" => Token::Null(span),
+        "Infinity" => Token::Float(f64::INFINITY, span),
+        "NaN" => Token::Float(f64::NAN, span),
+        word => Token::Ident(Cow::Borrowed(word), span),
+    }
+}
+
+// Skips a `//` or `/* */` comment whose `/` at `start` is already consumed.
+fn skip_comment(input: &str, start: usize, stream: &mut CharStream) -> Result<()> {
+    match stream.next() {
+        Some((_, '/')) => {
+            while stream.next_if(|&(_, ch)| ch != '\n').is_some() {}
+            Ok(())
+        }
+
+        Some((_, '*')) => {
+            let mut star = false;
+
+            loop {
+                match stream.next() {
+                    None => {
+                        return lex_error!(
+                            ErrorKind::UnexpectedEof("`*/` closing the comment"),
+                            start,
+                            input.len()
+                        );
                     }
 
-                    let token = if searching_for_float {
-                        let parsed = &input[start..end].parse::<f64>();
-                        let num = match parsed {
-                            Err(_e) => {
-                                return Err(format!(
-                                    "failed to convert str to f64 at span ({start}, {end})"
-                                ));
-                            } // invalid value somehow?,
-
-                            Ok(num) => num,
-                        };
-
-                        Token::Float(*num, Span::new(index, end))
-                    } else {
-                        let parsed = &input[start..end].parse::<i64>();
-                        let num = match parsed {
-                            Err(_e) => {
-                                return Err(format!(
-                                    "failed to convert str to i64 at span ({start}, {end})"
-                                ));
-                            } // invalid value somehow?
-
-                            Ok(num) => num,
-                        };
-
-                        Token::Int(*num, Span::new(index, end))
-                    };
-
-                    token
+                    Some((_, '/')) if star => return Ok(()),
+                    Some((_, ch)) => star = ch == '*',
                 }
+            }
+        }
 
-                ch if ch.is_ascii_alphabetic() => match ch {
-                    't' => {
-                        let val = &input[index..index + 4];
-                        if val != "true" {
-                            return Err(format!(
-                                "invalid bool (expt: true) value: {val} at span: {index}, {}",
-                                index + 2
-                            ));
-                        };
-
-                        stream.nth(3);
-
-                        Token::Bool(true, Span::new(index, index + 4))
-                    }
+        _ => lex_error!(ErrorKind::UnexpectedCharacter('/'), start, start + 1),
+    }
+}
 
-                    'f' => {
-                        let val = &input[index - 1..index + 4];
-                        if val != "false" {
-                            return Err(format!(
-                                "invalid bool (expt: false) value: {val} at span: {index}, {}",
-                                index + 2
-                            ));
-                        };
+// Skips the rest of a broken string up to and including its closing quote,
+// or up to the end of the line since strings can't span lines.
+fn skip_string_rest(stream: &mut CharStream, dialect: Dialect) {
+    while let Some((_, ch)) = stream.next_if(|&(_, ch)| ch != '\n' && ch != '\r') {
+        match ch {
+            '\\' => {
+                stream.next_if(|&(_, ch)| ch != '\n' && ch != '\r');
+            }
 
-                        stream.nth(4);
+            '"' => return,
+            '\'' if dialect == Dialect::Json5 => return,
+            _ => {}
+        }
+    }
+}
 
-                        Token::Bool(false, Span::new(index, index + 3))
-                    }
+// Length of the run of alphanumeric characters `text` starts with.
+fn word_len(text: &str) -> usize {
+    text.find(|ch: char| !ch.is_ascii_alphanumeric())
+        .unwrap_or(text.len())
+}
 
-                    'n' => {
-                        let val = &input[index..index + 4];
-                        if val == "null" {
-                            stream.nth(3);
-                            Token::Null(Span::new(index, index + 3))
-                        } else {
-                            return Err(format!(
-                                "invalid bool value: {val} at span: {index}, {}",
-                                index + 3
-                            ));
-                        }
-                    }
+// Lexes one of the literal names `true`, `false` or `null`
+// whose first character at `start` is already consumed.
+fn lex_keyword(
+    input: &str,
+    start: usize,
+    stream: &mut CharStream,
+    word: &'static str,
+) -> Result<Span> {
+    if !input[start..].starts_with(word) {
+        let len = word_len(&input[start..]);
+        return lex_error!(ErrorKind::InvalidLiteral, start, start + len);
+    }
 
-                    _ => {
-                        // In the name of diagnostics...
-                        let start = index;
-                        let mut end = index;
-
-                        loop {
-                            let (_index, ch) = match stream.next() {
-                                None => return Err(format!("unexpected eof at char {index}")),
-
-                                Some((index, ch)) => (index, ch),
-                            };
-
-                            end += 1;
-                            if ch == ',' {
-                                break;
-                            }
-                        }
-
-                        let val = &input[start..end];
-                        return Err(format!(
-                            "invalid bool value: {val} at span: {index}, {}",
-                            index + 2
-                        ));
+    for _ in 1..word.len() {
+        stream.next();
+    }
+
+    Ok(Span::new(start, start + word.len()))
+}
+
+// Lexes a number starting at `start` following the grammar of RFC 8259:
+// `-? (0 | [1-9][0-9]*) (. [0-9]+)? ([eE] [+-]? [0-9]+)?`
+// JSON5 also allows a leading `+`, a leading or trailing `.`,
+// hex integers and a signed `Infinity` or `NaN`.
+// The terminator after the number is left in the stream.
+fn lex_number<'l>(
+    input: &'l str,
+    start: usize,
+    stream: &mut CharStream<'l>,
+    dialect: Dialect,
+) -> Result<Token<'l>> {
+    let json5 = dialect == Dialect::Json5;
+    let mut is_float = false;
+
+    // the first character was consumed already
+    let mut leading = input.as_bytes()[start] as char;
+
+    if matches!(leading, '-' | '+') {
+        if json5 {
+            for (word, num) in [("Infinity", f64::INFINITY), ("NaN", f64::NAN)] {
+                if input[start + 1..].starts_with(word) {
+                    for _ in 0..word.len() {
+                        stream.next();
                     }
-                },
 
-                ch if ch.is_ascii_whitespace() => continue,
+                    let end = number_end(input, start, stream, dialect)?;
+                    let num = if leading == '-' { -num } else { num };
 
-                _ => unreachable!(),
-            };
+                    return Ok(Token::Float(num, Span::new(start, end)));
+                }
+            }
+        }
 
-            tokens.push(token);
+        match stream.next_if(|&(_, ch)| ch.is_ascii_digit() || (json5 && ch == '.')) {
+            Some((_, ch)) => leading = ch,
+            None => {
+                // a word right after the sign is spanned too, like `-Inf`
+                let len = word_len(&input[start + 1..]);
+                return lex_error!(
+                    ErrorKind::InvalidNumber("expected a digit after the sign"),
+                    start,
+                    start + 1 + len
+                );
+            }
         }
+    }
 
-        tokens.reverse();
-        let len = tokens.len();
+    if leading == '.' {
+        // only JSON5 gets here, the fraction is all there is
+        is_float = true;
 
-        let lexer = Self {
-            vec: tokens,
-            count: len,
-        };
+        if !skip_digits(stream) {
+            return lex_error!(
+                ErrorKind::InvalidNumber("expected a digit after the `.`"),
+                start,
+                start + 1
+            );
+        }
+    } else {
+        // integer part
+        if leading == '0' {
+            if json5 && let Some((index, _)) = stream.next_if(|&(_, ch)| matches!(ch, 'x' | 'X')) {
+                return lex_hex(input, start, index + 1, stream, dialect);
+            }
+
+            if let Some(&(index, ch)) = stream.peek()
+                && ch.is_ascii_digit()
+            {
+                return lex_error!(
+                    ErrorKind::InvalidNumber("numbers can't have leading zeros"),
+                    start,
+                    index + 1
+                );
+            }
+        } else {
+            while stream.next_if(|(_, ch)| ch.is_ascii_digit()).is_some() {}
+        }
+
+        // fraction, JSON5 lets it be empty
+        if let Some((index, _)) = stream.next_if(|&(_, ch)| ch == '.') {
+            is_float = true;
+
+            if !skip_digits(stream) && !json5 {
+                return lex_error!(
+                    ErrorKind::InvalidNumber("expected a digit after the `.`"),
+                    start,
+                    index + 1
+                );
+            }
+        }
+    }
+
+    // exponent
+    if let Some((index, _)) = stream.next_if(|&(_, ch)| ch == 'e' || ch == 'E') {
+        is_float = true;
+
+        stream.next_if(|&(_, ch)| ch == '+' || ch == '-');
 
-        Ok(lexer)
+        if !skip_digits(stream) {
+            return lex_error!(
+                ErrorKind::InvalidNumber("expected a digit in the exponent"),
+                start,
+                index + 1
+            );
+        }
     }
 
-    pub(crate) fn peek(&self, peek: usize) -> Option<&Token> {
-        if peek > self.count {
-            return None;
+    let end = number_end(input, start, stream, dialect)?;
+    let span = Span::new(start, end);
+    let text = &input[start..end];
+
+    // integers too big for an `i64` are kept as floats
+    let token = match text.parse::<i64>() {
+        Ok(num) if !is_float => Token::Int(num, span),
+        _ => match text.parse::<f64>() {
+            Ok(num) => Token::Float(num, span),
+            Err(_) => {
+                return lex_error!(
+                    ErrorKind::InvalidNumber("it can't be represented"),
+                    start,
+                    end
+                );
+            }
+        },
+    };
+
+    Ok(token)
+}
+
+// Lexes the digits of a JSON5 hex integer starting at `digits`,
+// `start` is where the whole number, sign included, starts.
+fn lex_hex<'l>(
+    input: &'l str,
+    start: usize,
+    digits: usize,
+    stream: &mut CharStream<'l>,
+    dialect: Dialect,
+) -> Result<Token<'l>> {
+    if stream.next_if(|(_, ch)| ch.is_ascii_hexdigit()).is_none() {
+        return lex_error!(
+            ErrorKind::InvalidNumber("expected a hex digit after the `0x`"),
+            start,
+            digits
+        );
+    }
+
+    while stream.next_if(|(_, ch)| ch.is_ascii_hexdigit()).is_some() {}
+
+    let end = number_end(input, start, stream, dialect)?;
+    let span = Span::new(start, end);
+    let negative = input.as_bytes()[start] == b'-';
+
+    // like decimal integers, ones too big for an `i64` are kept as floats
+    let token = match u64::from_str_radix(&input[digits..end], 16) {
+        Ok(num) => match i64::try_from(num) {
+            Ok(num) if negative => Token::Int(-num, span),
+            Ok(num) => Token::Int(num, span),
+            Err(_) if negative => Token::Float(-(num as f64), span),
+            Err(_) => Token::Float(num as f64, span),
+        },
+
+        Err(_) => {
+            let num = input[digits..end]
+                .chars()
+                .filter_map(|ch| ch.to_digit(16))
+                .fold(0.0, |acc, digit| acc * 16.0 + digit as f64);
+
+            Token::Float(if negative { -num } else { num }, span)
+        }
+    };
+
+    Ok(token)
+}
+
+// Finds the end of the number starting at `start`, which has to be followed
+// by whitespace, `,`, `]`, `}`, a comment or the end of input.
+fn number_end(
+    input: &str,
+    start: usize,
+    stream: &mut CharStream,
+    dialect: Dialect,
+) -> Result<usize> {
+    match stream.peek() {
+        None => Ok(input.len()),
+
+        Some(&(index, ch))
+            if dialect.is_whitespace(ch)
+                || matches!(ch, ',' | ']' | '}')
+                || (ch == '/' && dialect.comments()) =>
+        {
+            Ok(index)
         }
 
-        self.vec.get(peek)
+        Some(&(index, ch)) => lex_error!(
+            ErrorKind::InvalidNumber("it has to end at whitespace, `,`, `]` or `}`"),
+            start,
+            index + ch.len_utf8()
+        ),
     }
 }
 
-impl<'l> Iterator for Lexer<'l> {
-    type Item = Token<'l>;
+// Skips a run of digits, returns whether there was at least one.
+fn skip_digits(stream: &mut CharStream) -> bool {
+    let mut any = false;
 
-    fn next(&mut self) -> Option<Self::Item> {
-        if self.count == 0 {
-            return None;
+    while stream.next_if(|(_, ch)| ch.is_ascii_digit()).is_some() {
+        any = true;
+    }
+
+    any
+}
+
+// Lexes a string whose opening quote is at `start`,
+// decoding escapes as described in RFC 8259, or in the JSON5 spec for `Dialect::Json5`.
+// Strings without escapes are borrowed from `input`.
+// Decoding stops as soon as the string gets longer than `max_len` bytes.
+fn lex_string<'l>(
+    input: &'l str,
+    start: usize,
+    stream: &mut CharStream<'l>,
+    dialect: Dialect,
+    max_len: usize,
+) -> Result<(Cow<'l, str>, Span)> {
+    let json5 = dialect == Dialect::Json5;
+
+    // either `"` or, in JSON5, `'`
+    let quote = input.as_bytes()[start] as char;
+    let unterminated = match quote {
+        '"' => ErrorKind::UnexpectedEof("a closing `\"`"),
+        _ => ErrorKind::UnexpectedEof("a closing `'`"),
+    };
+
+    // only allocated once the first escape shows up
+    let mut owned: Option<String> = None;
+
+    loop {
+        let (index, ch) = match stream.next() {
+            None => return lex_error!(unterminated, start, input.len()),
+
+            Some(next) => next,
+        };
+
+        let len = owned.as_ref().map_or(index - start - 1, String::len);
+        if len > max_len {
+            return lex_error!(ErrorKind::StringTooLong(max_len), start, index);
         }
 
-        let item = self.vec.pop();
+        match ch {
+            ch if ch == quote => {
+                let string = match owned {
+                    None => Cow::Borrowed(&input[start + 1..index]),
+                    Some(string) => Cow::Owned(string),
+                };
+
+                return Ok((string, Span::new(start, index + 1)));
+            }
+
+            '\\' => {
+                let string = owned.get_or_insert_with(|| input[start + 1..index].to_string());
+
+                let escaped = match stream.next() {
+                    None => return lex_error!(unterminated, start, input.len()),
+
+                    Some((_, escaped)) => escaped,
+                };
 
-        self.count -= 1;
+                let decoded = match escaped {
+                    '"' => '"',
+                    '\\' => '\\',
+                    '/' => '/',
+                    'b' => '\u{08}',
+                    'f' => '\u{0C}',
+                    'n' => '\n',
+                    'r' => '\r',
+                    't' => '\t',
+                    'u' => unicode_escape(input, index, stream)?,
 
-        item
+                    // line continuations of multi-line JSON5 strings
+                    '\r' if json5 => {
+                        stream.next_if(|&(_, ch)| ch == '\n');
+                        continue;
+                    }
+                    '\n' | '\u{2028}' | '\u{2029}' if json5 => continue,
+
+                    'v' if json5 => '\u{0B}',
+                    '0' if json5 && stream.peek().is_none_or(|(_, ch)| !ch.is_ascii_digit()) => {
+                        '\0'
+                    }
+                    'x' if json5 => {
+                        let code = hex_digits(input, index, stream, 2)?;
+                        char::from_u32(code).expect("two hex digits are always a scalar value")
+                    }
+
+                    // JSON5 lets any other character but a digit escape itself
+                    ch if json5 && !ch.is_ascii_digit() => ch,
+
+                    ch => {
+                        return lex_error!(
+                            ErrorKind::InvalidEscape("unknown escape character"),
+                            index,
+                            index + 1 + ch.len_utf8()
+                        );
+                    }
+                };
+
+                string.push(decoded);
+            }
+
+            // JSON5 only forbids raw line breaks
+            '\n' | '\r' if json5 => {
+                return lex_error!(ErrorKind::ControlCharacter(ch), index, index + 1);
+            }
+
+            ch if (ch as u32) < 0x20 && !json5 => {
+                return lex_error!(ErrorKind::ControlCharacter(ch), index, index + 1);
+            }
+
+            ch => {
+                if let Some(ref mut string) = owned {
+                    string.push(ch);
+                }
+            }
+        }
+    }
+}
+
+// Decodes a `\uXXXX` escape whose `\` is at `start` and whose `u` is already consumed,
+// a high surrogate has to be followed by an escaped low surrogate.
+fn unicode_escape(input: &str, start: usize, stream: &mut CharStream) -> Result<char> {
+    let first = hex_digits(input, start, stream, 4)?;
+
+    let code = match first {
+        0xD800..=0xDBFF => {
+            let pair_start = start + 6;
+
+            let is_escape = stream.next_if(|&(_, ch)| ch == '\\').is_some()
+                && stream.next_if(|&(_, ch)| ch == 'u').is_some();
+
+            if !is_escape {
+                return lex_error!(ErrorKind::LoneSurrogate, start, pair_start);
+            }
+
+            let second = hex_digits(input, pair_start, stream, 4)?;
+            if !(0xDC00..=0xDFFF).contains(&second) {
+                return lex_error!(ErrorKind::LoneSurrogate, start, pair_start + 6);
+            }
+
+            0x10000 + ((first - 0xD800) << 10) + (second - 0xDC00)
+        }
+
+        0xDC00..=0xDFFF => {
+            return lex_error!(ErrorKind::LoneSurrogate, start, start + 6);
+        }
+
+        code => code,
+    };
+
+    // surrogates are handled above so this always holds a scalar value
+    Ok(char::from_u32(code).expect("invalid unicode scalar value"))
+}
+
+// Reads the `count` hex digits of a `\uXXXX` or `\xXX` escape starting at `start`.
+fn hex_digits(input: &str, start: usize, stream: &mut CharStream, count: usize) -> Result<u32> {
+    let mut code = 0;
+
+    for _ in 0..count {
+        let digit = match stream.next() {
+            None => {
+                return lex_error!(ErrorKind::UnexpectedEof("a hex digit"), start, input.len());
+            }
+
+            Some((index, ch)) => match ch.to_digit(16) {
+                Some(digit) => digit,
+                None => {
+                    return lex_error!(
+                        ErrorKind::InvalidEscape("expected a hex digit"),
+                        index,
+                        index + ch.len_utf8()
+                    );
+                }
+            },
+        };
+
+        code = code * 16 + digit;
     }
+
+    Ok(code)
 }
@@ -0,0 +1,33 @@
+// lets the derive macros refer to `::iovera` from inside this crate too
+extern crate self as iovera;
+
+mod cst;
+mod error;
+mod event;
+mod layout;
+mod lexer;
+mod line_index;
+mod map;
+mod options;
+mod parser;
+mod push;
+mod reader;
+mod ser;
+mod structural;
+mod traits;
+mod value;
+
+pub use cst::{Document, Element, Member, Node, NodeKind};
+pub use error::{Error, ErrorKind, SerializeError, Snippet};
+pub use event::{Event, Events, SpannedEvent};
+pub use iovera_macros::{JsonDeserialize, JsonSerialize};
+pub use lexer::{Dialect, Lexer, Span, Token};
+pub use line_index::{Encoding, LineCol, LineIndex};
+pub use map::{Entry, Map, MultiMap};
+pub use options::{DuplicateKeys, ParseOptions, SerializeOptions};
+pub use parser::{Parser, Recovered};
+pub use push::{Progress, PushParser};
+pub use reader::ReadLexer;
+pub use ser::{Serializer, to_canonical_vec, to_string, to_string_with, to_writer, to_writer_with};
+pub use traits::{JsonDeserialize, JsonSerialize, from_str};
+pub use value::{JsonValue, Number};
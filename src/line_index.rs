@@ -0,0 +1,140 @@
+/*
+    Mapping byte offsets to lines and columns and back.
+*/
+
+use crate::lexer::Span;
+
+/// How columns are counted, editors mostly want UTF-16 code units.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Encoding {
+    Utf8,
+    Utf16,
+
+    /// One column per `char`, as `Error` counts them.
+    Chars,
+}
+
+impl Encoding {
+    // Columns `ch` takes.
+    fn width(self, ch: char) -> usize {
+        match self {
+            Self::Utf8 => ch.len_utf8(),
+            Self::Utf16 => ch.len_utf16(),
+            Self::Chars => 1,
+        }
+    }
+}
+
+/// A position in the source, both fields start at 0.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub struct LineCol {
+    pub line: usize,
+    pub column: usize,
+}
+
+impl LineCol {
+    pub fn new(line: usize, column: usize) -> Self {
+        Self { line, column }
+    }
+}
+
+/// Start offsets of every line of a source, built once
+/// so each conversion only needs a binary search and a scan of one line.
+///
+/// Lines end at `\n`, a `\r` before it counts as part of the line.
+#[derive(Debug, Clone)]
+pub struct LineIndex<'s> {
+    source: &'s str,
+
+    // always starts with 0
+    line_starts: Vec<usize>,
+}
+
+impl<'s> LineIndex<'s> {
+    pub fn new(source: &'s str) -> Self {
+        let newlines = source
+            .bytes()
+            .enumerate()
+            .filter(|&(_, byte)| byte == b'\n')
+            .map(|(index, _)| index + 1);
+
+        Self {
+            source,
+            line_starts: std::iter::once(0).chain(newlines).collect(),
+        }
+    }
+
+    pub fn line_count(&self) -> usize {
+        self.line_starts.len()
+    }
+
+    /// Position of the byte `offset`.
+    /// Offsets past the end are clamped to it, offsets inside a character to its start.
+    pub fn position(&self, offset: usize, encoding: Encoding) -> LineCol {
+        let mut offset = offset.min(self.source.len());
+        while !self.source.is_char_boundary(offset) {
+            offset -= 1;
+        }
+
+        let line = self.line_starts.partition_point(|&start| start <= offset) - 1;
+        let before = &self.source[self.line_starts[line]..offset];
+
+        let column = match encoding {
+            Encoding::Utf8 => before.len(),
+            _ => before.chars().map(|ch| encoding.width(ch)).sum(),
+        };
+
+        LineCol { line, column }
+    }
+
+    /// Positions of the start and the end of `span`.
+    pub fn span_position(&self, span: Span, encoding: Encoding) -> (LineCol, LineCol) {
+        (
+            self.position(span.start(), encoding),
+            self.position(span.end(), encoding),
+        )
+    }
+
+    /// Byte offset of `pos`, the inverse of `position`.
+    /// Returns `None` if the line doesn't exist,
+    /// the column is past the end of the line or inside a character.
+    pub fn offset(&self, pos: LineCol, encoding: Encoding) -> Option<usize> {
+        let start = *self.line_starts.get(pos.line)?;
+        let end = self
+            .line_starts
+            .get(pos.line + 1)
+            .map_or(self.source.len(), |&next| next - 1);
+        let text = &self.source[start..end];
+
+        let len = match encoding {
+            Encoding::Utf8 => text.is_char_boundary(pos.column).then_some(pos.column)?,
+
+            _ => {
+                let mut units = 0;
+                let mut chars = text.char_indices();
+
+                loop {
+                    if units == pos.column {
+                        break chars.offset();
+                    }
+
+                    if units > pos.column {
+                        return None;
+                    }
+
+                    units += encoding.width(chars.next()?.1);
+                }
+            }
+        };
+
+        Some(start + len)
+    }
+
+    /// Span between the positions `start` and `end`, the inverse of `span_position`.
+    pub fn span(&self, start: LineCol, end: LineCol, encoding: Encoding) -> Option<Span> {
+        let start = self.offset(start, encoding)?;
+        let end = self.offset(end, encoding)?;
+
+        (start <= end).then(|| Span::new(start, end))
+    }
+}
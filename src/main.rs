@@ -1,13 +1,9 @@
-mod lexer;
-mod parser;
-mod traits;
-
-use ioveca_macros;
-use lexer::Lexer;
+use iovera::{Dialect, Parser};
 
 fn main() {
     let json = concat!(
         "{\n",
+        "    // trailing commas and comments need the JSONC dialect\n",
         "    \"stringvalue\": \"some string\",\n",
         "    \"intvalue\": 123456789,\n",
         "    \"floatvalue\": 1.23456789,\n",
@@ -17,10 +13,11 @@ fn main() {
         "}",
     );
 
-    println!("JSON to test the Lexer: \n{json}\n");
+    println!("JSON to test the Parser: \n{json}\n");
     println!("Text in debug form: {json:#?}\n");
 
-    let lexed = Lexer::new(json).unwrap();
-
-    println!("Processed data:\n\n{lexed:#?}");
+    match Parser::with_dialect(json, Dialect::Jsonc).parse() {
+        Ok(parsed) => println!("Processed data:\n\n{parsed:#?}"),
+        Err(err) => println!("{}", err.snippet(json)),
+    }
 }
@@ -0,0 +1,367 @@
+/*
+    Objects of the document model, keeping the order of their keys.
+*/
+
+use std::cmp::Ordering;
+use std::collections::HashMap;
+use std::fmt;
+
+use crate::value::JsonValue;
+
+/// A JSON object, keys are unique.
+///
+/// Entries stay in the order they were inserted, the order of the source for parsed objects,
+/// while lookups by key go through a hash index.
+/// A map created with `Map::sorted` keeps its keys sorted instead.
+///
+/// Values replaced through `Map::append` are kept aside as duplicates,
+/// only `Map::multi` sees them.
+#[derive(Clone, Default)]
+pub struct Map {
+    entries: Vec<(String, JsonValue)>,
+
+    // position of every key in `entries`
+    index: HashMap<String, usize>,
+
+    // new keys go to their place in key order rather than at the end
+    sorted: bool,
+
+    // values shadowed by a later one under the same key, in the order they were appended
+    duplicates: HashMap<String, Vec<JsonValue>>,
+}
+
+impl Map {
+    /// Creates an empty `Map` keeping insertion order.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Creates an empty `Map` keeping its keys sorted,
+    /// inserting a new key takes linear time.
+    pub fn sorted() -> Self {
+        Self {
+            sorted: true,
+            ..Self::default()
+        }
+    }
+
+    /// Whether new keys go to their place in key order, see `Map::sorted`.
+    pub fn is_sorted(&self) -> bool {
+        self.sorted
+    }
+
+    /// Sorts the keys and keeps them sorted from then on, as if created with `Map::sorted`.
+    pub fn keep_sorted(&mut self) {
+        self.sort_keys();
+        self.sorted = true;
+    }
+
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    pub fn contains_key(&self, key: &str) -> bool {
+        self.index.contains_key(key)
+    }
+
+    pub fn get(&self, key: &str) -> Option<&JsonValue> {
+        self.index_of(key).map(|index| &self.entries[index].1)
+    }
+
+    pub fn get_mut(&mut self, key: &str) -> Option<&mut JsonValue> {
+        self.index_of(key).map(|index| &mut self.entries[index].1)
+    }
+
+    /// Position of `key` in the order of the map.
+    pub fn index_of(&self, key: &str) -> Option<usize> {
+        self.index.get(key).copied()
+    }
+
+    /// Entry at position `index` in the order of the map.
+    pub fn get_index(&self, index: usize) -> Option<(&String, &JsonValue)> {
+        self.entries.get(index).map(|(key, value)| (key, value))
+    }
+
+    /// Inserts a value, returning the one previously stored under `key`.
+    /// A key already present keeps its position, a new one goes last
+    /// or to its place in key order if the map is sorted.
+    pub fn insert(
+        &mut self,
+        key: impl Into<String>,
+        value: impl Into<JsonValue>,
+    ) -> Option<JsonValue> {
+        let key = key.into();
+        let value = value.into();
+
+        match self.index_of(&key) {
+            Some(index) => Some(std::mem::replace(&mut self.entries[index].1, value)),
+
+            None => {
+                self.insert_new(key, value);
+                None
+            }
+        }
+    }
+
+    /// Inserts a value, keeping the one previously stored under `key` as a duplicate.
+    pub fn append(&mut self, key: impl Into<String>, value: impl Into<JsonValue>) {
+        let key = key.into();
+
+        if let Some(old) = self.insert(key.as_str(), value) {
+            self.duplicates.entry(key).or_default().push(old);
+        }
+    }
+
+    /// Removes `key` along with its duplicates, returning the value `get` would give.
+    /// The entries after it move up, which takes linear time.
+    pub fn remove(&mut self, key: &str) -> Option<JsonValue> {
+        let index = self.index_of(key)?;
+        let (key, value) = self.entries.remove(index);
+
+        self.index.remove(&key);
+        self.duplicates.remove(&key);
+        self.reindex(index..self.entries.len());
+
+        Some(value)
+    }
+
+    /// Removes `key` along with its duplicates in constant time,
+    /// the last entry takes its position.
+    pub fn swap_remove(&mut self, key: &str) -> Option<JsonValue> {
+        let index = self.index_of(key)?;
+        let (key, value) = self.entries.swap_remove(index);
+
+        self.index.remove(&key);
+        self.duplicates.remove(&key);
+        self.reindex(index..self.entries.len().min(index + 1));
+
+        // the moved entry breaks the order of a sorted map
+        if self.entries.len() > index {
+            self.sorted = false;
+        }
+
+        Some(value)
+    }
+
+    /// Moves the entry at position `from` to position `to`, shifting the ones in between.
+    /// A sorted map keeps insertion order from then on.
+    ///
+    /// # Panics
+    /// If either position is out of bounds.
+    pub fn move_index(&mut self, from: usize, to: usize) {
+        let len = self.entries.len();
+        assert!(
+            from < len && to < len,
+            "cannot move entry {from} to {to} in a map of length {len}"
+        );
+
+        match from.cmp(&to) {
+            Ordering::Less => self.entries[from..=to].rotate_left(1),
+            Ordering::Greater => self.entries[to..=from].rotate_right(1),
+            Ordering::Equal => return,
+        }
+
+        self.reindex(from.min(to)..from.max(to) + 1);
+        self.sorted = false;
+    }
+
+    /// Sorts the entries by key, a sorted map stays sorted anyway.
+    pub fn sort_keys(&mut self) {
+        self.entries.sort_unstable_by(|(a, _), (b, _)| a.cmp(b));
+        self.reindex(0..self.entries.len());
+    }
+
+    /// Sorts the entries with `compare` given the key and value of two entries.
+    /// A sorted map keeps insertion order from then on.
+    pub fn sort_by<F>(&mut self, mut compare: F)
+    where
+        F: FnMut(&str, &JsonValue, &str, &JsonValue) -> Ordering,
+    {
+        self.entries
+            .sort_by(|(key_a, a), (key_b, b)| compare(key_a, a, key_b, b));
+        self.reindex(0..self.entries.len());
+        self.sorted = false;
+    }
+
+    /// View of the map with every value appended under a key, duplicates included.
+    pub fn multi(&self) -> MultiMap<'_> {
+        MultiMap { map: self }
+    }
+
+    /// Gets the entry of `key` for in-place manipulation.
+    pub fn entry(&mut self, key: impl Into<String>) -> Entry<'_> {
+        Entry {
+            map: self,
+            key: key.into(),
+        }
+    }
+
+    /// Entries in the order of the map.
+    pub fn iter(&self) -> impl Iterator<Item = (&String, &JsonValue)> {
+        self.entries.iter().map(|(key, value)| (key, value))
+    }
+
+    pub fn iter_mut(&mut self) -> impl Iterator<Item = (&String, &mut JsonValue)> {
+        self.entries.iter_mut().map(|(key, value)| (&*key, value))
+    }
+
+    pub fn keys(&self) -> impl Iterator<Item = &String> {
+        self.entries.iter().map(|(key, _)| key)
+    }
+
+    pub fn values(&self) -> impl Iterator<Item = &JsonValue> {
+        self.entries.iter().map(|(_, value)| value)
+    }
+
+    // Adds a key that isn't in the map yet, returning its position.
+    fn insert_new(&mut self, key: String, value: JsonValue) -> usize {
+        let index = match self.sorted {
+            true => self
+                .entries
+                .partition_point(|(other, _)| other.as_str() < key.as_str()),
+            false => self.entries.len(),
+        };
+
+        self.entries.insert(index, (key, value));
+        self.reindex(index..self.entries.len());
+
+        index
+    }
+
+    // Brings the positions of the entries in `range` up to date in the index.
+    fn reindex(&mut self, range: std::ops::Range<usize>) {
+        for index in range {
+            let key = &self.entries[index].0;
+
+            match self.index.get_mut(key) {
+                Some(position) => *position = index,
+                None => {
+                    self.index.insert(key.clone(), index);
+                }
+            }
+        }
+    }
+}
+
+impl PartialEq for Map {
+    /// Maps are equal with the same entries, in any order.
+    fn eq(&self, other: &Self) -> bool {
+        self.len() == other.len()
+            && self
+                .iter()
+                .all(|(key, value)| other.get(key) == Some(value))
+            && self.duplicates == other.duplicates
+    }
+}
+
+impl fmt::Debug for Map {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_map().entries(self.iter()).finish()
+    }
+}
+
+impl<K: Into<String>, V: Into<JsonValue>> FromIterator<(K, V)> for Map {
+    fn from_iter<T: IntoIterator<Item = (K, V)>>(iter: T) -> Self {
+        let mut map = Map::new();
+        iter.into_iter().for_each(|(key, value)| {
+            map.insert(key, value);
+        });
+
+        map
+    }
+}
+
+impl IntoIterator for Map {
+    type Item = (String, JsonValue);
+    type IntoIter = std::vec::IntoIter<(String, JsonValue)>;
+
+    /// Entries in the order of the map, without duplicates.
+    fn into_iter(self) -> Self::IntoIter {
+        self.entries.into_iter()
+    }
+}
+
+/// A `Map` seen as a multi-map, see `Map::multi`.
+#[derive(Debug, Clone, Copy)]
+pub struct MultiMap<'m> {
+    map: &'m Map,
+}
+
+impl<'m> MultiMap<'m> {
+    /// Number of values, duplicates included.
+    pub fn len(&self) -> usize {
+        self.map.len() + self.map.duplicates.values().map(Vec::len).sum::<usize>()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.map.is_empty()
+    }
+
+    /// Whether any key has more than one value.
+    pub fn has_duplicates(&self) -> bool {
+        !self.map.duplicates.is_empty()
+    }
+
+    /// Every value under `key` in the order they were appended,
+    /// the last one is the one `Map::get` gives.
+    pub fn get_all(&self, key: &str) -> impl Iterator<Item = &'m JsonValue> + use<'m> {
+        let duplicates = self.map.duplicates.get(key).into_iter().flatten();
+
+        duplicates.chain(self.map.get(key))
+    }
+
+    /// Every key and value, keys in the order of the map
+    /// and the values of a key in the order they were appended.
+    pub fn iter(&self) -> impl Iterator<Item = (&'m String, &'m JsonValue)> + use<'m> {
+        let map = self.map;
+
+        map.iter().flat_map(move |(key, value)| {
+            let duplicates = map.duplicates.get(key).into_iter().flatten();
+
+            duplicates.chain(Some(value)).map(move |value| (key, value))
+        })
+    }
+}
+
+/// A view into a single key of a `Map`, see `Map::entry`.
+pub struct Entry<'m> {
+    map: &'m mut Map,
+    key: String,
+}
+
+impl<'m> Entry<'m> {
+    pub fn key(&self) -> &str {
+        &self.key
+    }
+
+    /// Inserts `default` if the key is vacant and returns the stored value.
+    pub fn or_insert(self, default: impl Into<JsonValue>) -> &'m mut JsonValue {
+        self.or_insert_with(|| default.into())
+    }
+
+    pub fn or_insert_with<F: FnOnce() -> JsonValue>(self, default: F) -> &'m mut JsonValue {
+        let index = match self.map.index_of(&self.key) {
+            Some(index) => index,
+            None => self.map.insert_new(self.key, default()),
+        };
+
+        &mut self.map.entries[index].1
+    }
+
+    /// Inserts `JsonValue::Null` if the key is vacant.
+    pub fn or_null(self) -> &'m mut JsonValue {
+        self.or_insert_with(|| JsonValue::Null)
+    }
+
+    pub fn and_modify<F: FnOnce(&mut JsonValue)>(self, function: F) -> Self {
+        if let Some(value) = self.map.get_mut(&self.key) {
+            function(value);
+        }
+
+        self
+    }
+}
@@ -0,0 +1,262 @@
+/*
+    Options shared by the lexers and parsers, and those of the `Serializer`.
+*/
+
+use crate::lexer::Dialect;
+
+/// What the `Parser` does with a key written twice in the same object.
+///
+/// The streaming parsers give every key as it comes and leave duplicates to the consumer.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum DuplicateKeys {
+    /// Fails with `ErrorKind::DuplicateKey` at the second key, carrying the span of the first.
+    Error,
+
+    /// Keeps the first value, the later ones are parsed and dropped.
+    FirstWins,
+
+    /// Keeps the last value.
+    #[default]
+    LastWins,
+
+    /// Keeps every value, the last one as usual and all of them through `Map::multi`.
+    /// Types deserialized straight from the `Parser` keep the last one.
+    KeepAll,
+}
+
+/// The `Dialect` to accept along with limits guarding against hostile input.
+///
+/// Going over a limit is an error with its own `ErrorKind`.
+/// `Default` only limits the nesting depth, to keep the recursive `Parser` off the end of the stack.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ParseOptions {
+    pub dialect: Dialect,
+
+    /// Most containers open at once.
+    pub max_depth: usize,
+
+    /// Longest string in bytes once decoded, keys included.
+    pub max_string_len: usize,
+
+    /// Longest object key in bytes once decoded.
+    pub max_key_len: usize,
+
+    /// Most tokens in the whole input.
+    pub max_tokens: usize,
+
+    /// Most members of a single object or elements of a single array.
+    pub max_members: usize,
+
+    /// Largest input in bytes.
+    pub max_input_len: usize,
+
+    pub duplicate_keys: DuplicateKeys,
+
+    /// Builds objects as sorted `Map`s, see `Map::sorted`, rather than in the order of the input.
+    pub sorted_maps: bool,
+}
+
+impl ParseOptions {
+    /// Strict JSON with only the nesting depth limited, to 128, the last of duplicate keys wins.
+    pub fn new() -> Self {
+        Self::unlimited().max_depth(128)
+    }
+
+    /// Strict JSON without any limits, the last of duplicate keys wins.
+    pub fn unlimited() -> Self {
+        Self {
+            dialect: Dialect::Strict,
+            max_depth: usize::MAX,
+            max_string_len: usize::MAX,
+            max_key_len: usize::MAX,
+            max_tokens: usize::MAX,
+            max_members: usize::MAX,
+            max_input_len: usize::MAX,
+            duplicate_keys: DuplicateKeys::LastWins,
+            sorted_maps: false,
+        }
+    }
+
+    pub fn dialect(mut self, dialect: Dialect) -> Self {
+        self.dialect = dialect;
+        self
+    }
+
+    pub fn max_depth(mut self, max: usize) -> Self {
+        self.max_depth = max;
+        self
+    }
+
+    pub fn max_string_len(mut self, max: usize) -> Self {
+        self.max_string_len = max;
+        self
+    }
+
+    pub fn max_key_len(mut self, max: usize) -> Self {
+        self.max_key_len = max;
+        self
+    }
+
+    pub fn max_tokens(mut self, max: usize) -> Self {
+        self.max_tokens = max;
+        self
+    }
+
+    pub fn max_members(mut self, max: usize) -> Self {
+        self.max_members = max;
+        self
+    }
+
+    pub fn max_input_len(mut self, max: usize) -> Self {
+        self.max_input_len = max;
+        self
+    }
+
+    pub fn duplicate_keys(mut self, policy: DuplicateKeys) -> Self {
+        self.duplicate_keys = policy;
+        self
+    }
+
+    pub fn sorted_maps(mut self, sorted: bool) -> Self {
+        self.sorted_maps = sorted;
+        self
+    }
+}
+
+impl Default for ParseOptions {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// How the `Serializer` lays out and escapes what it writes.
+///
+/// `Default` is compact, nothing is written between tokens
+/// and only what RFC 8259 requires is escaped.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SerializeOptions {
+    /// Written once per level of nesting at the start of every line,
+    /// without it everything is written on a single line.
+    pub indent: Option<String>,
+
+    /// Ends every line, only written with an `indent`.
+    pub newline: String,
+
+    pub space_after_colon: bool,
+
+    /// Writes the members of every object in key order, which holds them back
+    /// until the end of the object.
+    pub sort_keys: bool,
+
+    /// Escapes every character outside of ASCII as `\uXXXX`, as a surrogate pair above U+FFFF.
+    pub ascii_only: bool,
+
+    /// Escapes `<`, `>` and `&` as `\uXXXX`, for embedding in HTML.
+    pub escape_html: bool,
+
+    /// Keeps every object and array on one line when it fits within this many columns,
+    /// only with an `indent`. The whole value is held back until its end.
+    pub max_width: Option<usize>,
+
+    /// Levels of nested containers that may still be kept on one line,
+    /// `1` only for those holding nothing but scalars.
+    pub max_inline_depth: usize,
+
+    /// Breaks arrays of scalars that don't fit on one line into as many elements
+    /// per line as fit, rather than one per line.
+    pub fill_scalar_arrays: bool,
+
+    /// Writes the canonical form of RFC 8785, the same bytes for the same data:
+    /// no whitespace, keys sorted by UTF-16 code units, minimal escaping
+    /// and every number as the shortest ECMAScript double that reads back the same.
+    /// Takes precedence over every other option.
+    pub canonical: bool,
+}
+
+impl SerializeOptions {
+    /// Everything on a single line without any spaces.
+    pub fn compact() -> Self {
+        Self {
+            indent: None,
+            newline: String::from("\n"),
+            space_after_colon: false,
+            sort_keys: false,
+            ascii_only: false,
+            escape_html: false,
+            max_width: None,
+            max_inline_depth: usize::MAX,
+            fill_scalar_arrays: false,
+            canonical: false,
+        }
+    }
+
+    /// Every entry on a line of its own indented by two spaces, a space after colons.
+    pub fn pretty() -> Self {
+        Self::compact().indent("  ").space_after_colon(true)
+    }
+
+    /// Like `pretty`, but keeping objects and arrays on one line when they fit within `width` columns.
+    pub fn smart(width: usize) -> Self {
+        Self::pretty().max_width(width)
+    }
+
+    /// The canonical form of RFC 8785, for hashing and signatures.
+    pub fn canonical() -> Self {
+        Self {
+            canonical: true,
+            sort_keys: true,
+            ..Self::compact()
+        }
+    }
+
+    pub fn indent(mut self, indent: impl Into<String>) -> Self {
+        self.indent = Some(indent.into());
+        self
+    }
+
+    pub fn newline(mut self, newline: impl Into<String>) -> Self {
+        self.newline = newline.into();
+        self
+    }
+
+    pub fn space_after_colon(mut self, space: bool) -> Self {
+        self.space_after_colon = space;
+        self
+    }
+
+    pub fn sort_keys(mut self, sort: bool) -> Self {
+        self.sort_keys = sort;
+        self
+    }
+
+    pub fn ascii_only(mut self, ascii: bool) -> Self {
+        self.ascii_only = ascii;
+        self
+    }
+
+    pub fn escape_html(mut self, escape: bool) -> Self {
+        self.escape_html = escape;
+        self
+    }
+
+    pub fn max_width(mut self, width: usize) -> Self {
+        self.max_width = Some(width);
+        self
+    }
+
+    pub fn max_inline_depth(mut self, depth: usize) -> Self {
+        self.max_inline_depth = depth;
+        self
+    }
+
+    pub fn fill_scalar_arrays(mut self, fill: bool) -> Self {
+        self.fill_scalar_arrays = fill;
+        self
+    }
+}
+
+impl Default for SerializeOptions {
+    fn default() -> Self {
+        Self::compact()
+    }
+}
@@ -1,6 +1,997 @@
-use crate::lexer::{Lexer, Token};
+/*
+    Recursive descent parser turning the `Lexer`'s tokens into a `JsonValue`.
+*/
 
-struct Parser<'p> {
+use std::borrow::Cow;
+use std::collections::HashMap;
+
+use crate::error::{Error, ErrorKind};
+use crate::lexer::{Dialect, Lexer, Span, Token};
+use crate::map::Map;
+use crate::options::{DuplicateKeys, ParseOptions};
+use crate::traits::JsonDeserialize;
+use crate::value::{JsonValue, Number};
+
+type Result<T> = std::result::Result<T, Error>;
+
+// errors whose span is up to the caller
+type KindResult<T> = std::result::Result<T, ErrorKind>;
+
+macro_rules! wrong_token {
+    ($parser:expr, $token:expr, $expected:expr) => {{
+        let token = $token;
+        let kind = $crate::error::ErrorKind::UnexpectedToken {
+            found: $crate::parser::describe(&token),
+            expected: $expected,
+        };
+
+        Err($parser.error(kind, *token.span()))
+    }};
+}
+
+pub(crate) use wrong_token;
+
+pub struct Parser<'p> {
     lexer: Lexer<'p>,
-    last_token: Token,
+    last_span: Option<Span>,
+
+    // entries read so far of every container currently open, innermost last
+    open: Vec<usize>,
+
+    // keys seen so far and where, for each container in `open`,
+    // only filled for objects under a `DuplicateKeys` policy that needs it
+    keys: Vec<HashMap<String, Span>>,
+}
+
+impl<'p> Parser<'p> {
+    /// Creates a new `Parser` over `input`,
+    /// tokens are lexed as the parser goes.
+    pub fn new(input: &'p str) -> Self {
+        Self::from_lexer(Lexer::new(input))
+    }
+
+    /// Creates a new `Parser` over `input` accepting the given `Dialect`.
+    pub fn with_dialect(input: &'p str, dialect: Dialect) -> Self {
+        Self::from_lexer(Lexer::with_dialect(input, dialect))
+    }
+
+    /// Creates a new `Parser` over `input` enforcing the limits of `options`.
+    pub fn with_options(input: &'p str, options: ParseOptions) -> Self {
+        Self::from_lexer(Lexer::with_options(input, options))
+    }
+
+    /// Creates a new `Parser` over an existing `Lexer`, using its options.
+    pub(crate) fn from_lexer(lexer: Lexer<'p>) -> Self {
+        Self {
+            lexer,
+            last_span: None,
+            open: Vec::new(),
+            keys: Vec::new(),
+        }
+    }
+
+    /// Parses the whole input as a single JSON document,
+    /// anything left after the root value is an error.
+    pub fn parse(mut self) -> Result<JsonValue> {
+        let value = self.value()?;
+        self.finish()?;
+
+        Ok(value)
+    }
+
+    /// Parses the whole input as a single JSON document like `parse`, but going on after errors
+    /// to find every problem in one go rather than only the first.
+    ///
+    /// Lexing resumes after a broken token, and after a broken entry parsing resumes
+    /// at the next `,` or closing bracket of the same container.
+    /// A forgotten comma or colon is reported and parsing goes on as if it was there.
+    /// Gives the tree with `null` where values couldn't be parsed, the spans of those placeholders
+    /// and every error, all in the order of the input. Going over a limit of `ParseOptions`
+    /// stops everything at once, that error being the last.
+    pub fn parse_recovering(mut self) -> Recovered {
+        Recovery {
+            parser: &mut self,
+            errors: Vec::new(),
+            placeholders: Vec::new(),
+            pending: None,
+            fatal: false,
+        }
+        .run()
+    }
+
+    /// Reads the whole input as a `T`,
+    /// anything left after the root value is an error.
+    pub fn deserialize<T: JsonDeserialize>(mut self) -> Result<T> {
+        let value = T::deserialize(&mut self)?;
+        self.finish()?;
+
+        Ok(value)
+    }
+
+    fn finish(&mut self) -> Result<()> {
+        match self.next()? {
+            None => Ok(()),
+            Some(token) => wrong_token!(self, token, "end of input after the root value"),
+        }
+    }
+
+    /// Moves one token forward, remembering its span.
+    pub(crate) fn next(&mut self) -> Result<Option<Token<'p>>> {
+        let token = self.lexer.next().transpose()?;
+
+        if let Some(ref token) = token {
+            self.last_span = Some(*token.span());
+        }
+
+        Ok(token)
+    }
+
+    /// Moves one token forward,
+    /// running out of tokens is an error, `expected` says what was wanted instead.
+    pub(crate) fn eof_next(&mut self, expected: &'static str) -> Result<Token<'p>> {
+        match self.next()? {
+            None => {
+                let end = self.lexer.input().len();
+                Err(self.error(ErrorKind::UnexpectedEof(expected), Span::new(end, end)))
+            }
+
+            Some(token) => Ok(token),
+        }
+    }
+
+    /// Peeks at the next token without consuming it.
+    pub(crate) fn peek(&mut self) -> Result<Option<&Token<'p>>> {
+        self.lexer.peek(0)
+    }
+
+    /// Creates an error of `kind` at `span` of the input,
+    /// for `JsonDeserialize` implementations to report their own errors.
+    pub fn error(&self, kind: ErrorKind, span: Span) -> Error {
+        self.lexer.error(kind, span)
+    }
+
+    /// Text of the input under `span`.
+    pub(crate) fn text(&self, span: Span) -> &'p str {
+        span.slice(self.lexer.input()).unwrap_or_default()
+    }
+
+    /// Span of the last token obtained.
+    pub fn get_last_span(&self) -> Span {
+        self.last_span.unwrap_or(Span::new(0, 0))
+    }
+
+    /// Consumes the `{` opening an object, returning its span.
+    pub fn begin_object(&mut self) -> Result<Span> {
+        match self.eof_next("an object")? {
+            Token::LeftBracket(span) => self.enter(span),
+            token => wrong_token!(self, token, "an object"),
+        }
+    }
+
+    /// Reads the next key of an object along with the `:` after it,
+    /// `first` tells whether the `{` was the last token consumed.
+    /// Returns `None` once the closing `}` is consumed.
+    ///
+    /// Duplicate keys are dealt with according to `ParseOptions::duplicate_keys`,
+    /// under `DuplicateKeys::FirstWins` the values of later ones are skipped here.
+    pub fn next_key(&mut self, mut first: bool) -> Result<Option<(String, Span)>> {
+        loop {
+            let (key, span) = match self.key(first)? {
+                Some(key) => key,
+                None => {
+                    self.leave();
+                    return Ok(None);
+                }
+            };
+
+            first = false;
+
+            let max = self.lexer.options().max_key_len;
+            if key.len() > max {
+                return Err(self.error(ErrorKind::KeyTooLong(max), span));
+            }
+
+            self.count_entry(span)?;
+
+            match self.unique_key(&key, span)? {
+                true => return Ok(Some((key, span))),
+
+                // the value of the first one stays
+                false => {
+                    self.value()?;
+                }
+            }
+        }
+    }
+
+    fn key(&mut self, first: bool) -> Result<Option<(String, Span)>> {
+        if first {
+            if let Some(Token::RightBracket(_)) = self.peek()? {
+                self.next()?;
+                return Ok(None);
+            }
+        } else {
+            match self.eof_next("`,` or `}`")? {
+                Token::Comma(_) => {}
+                Token::RightBracket(_) => return Ok(None),
+                token => return wrong_token!(self, token, "`,` or `}`"),
+            }
+
+            if self.lexer.dialect().trailing_commas()
+                && let Some(Token::RightBracket(_)) = self.peek()?
+            {
+                self.next()?;
+                return Ok(None);
+            }
+        }
+
+        let key = match self.eof_next("a string key")? {
+            Token::String(key, span) => (key.into_owned(), span),
+
+            token => match ident_key(self.lexer.dialect(), &token, self.text(*token.span())) {
+                Some(key) => (key.into_owned(), *token.span()),
+                None => return wrong_token!(self, token, "a string key"),
+            },
+        };
+
+        match self.eof_next("`:`")? {
+            Token::Colon(_) => {}
+            token => return wrong_token!(self, token, "`:`"),
+        }
+
+        Ok(Some(key))
+    }
+
+    /// Consumes the `[` opening an array, returning its span.
+    pub fn begin_array(&mut self) -> Result<Span> {
+        match self.eof_next("an array")? {
+            Token::LeftSquareBracket(span) => self.enter(span),
+            token => wrong_token!(self, token, "an array"),
+        }
+    }
+
+    /// Moves to the next element of an array,
+    /// `first` tells whether the `[` was the last token consumed.
+    /// Returns `false` once the closing `]` is consumed.
+    pub fn next_element(&mut self, first: bool) -> Result<bool> {
+        let more = self.element(first)?;
+
+        match more {
+            true => {
+                // the element itself is blamed for going over the limit
+                let span = match self.peek()? {
+                    Some(token) => *token.span(),
+                    None => self.get_last_span(),
+                };
+
+                self.count_entry(span)?;
+            }
+
+            false => self.leave(),
+        }
+
+        Ok(more)
+    }
+
+    fn element(&mut self, first: bool) -> Result<bool> {
+        if first {
+            if let Some(Token::RightSquareBracket(_)) = self.peek()? {
+                self.next()?;
+                return Ok(false);
+            }
+
+            return Ok(true);
+        }
+
+        match self.eof_next("`,` or `]`")? {
+            Token::Comma(_) => {}
+            Token::RightSquareBracket(_) => return Ok(false),
+            token => return wrong_token!(self, token, "`,` or `]`"),
+        }
+
+        if self.lexer.dialect().trailing_commas()
+            && let Some(Token::RightSquareBracket(_)) = self.peek()?
+        {
+            self.next()?;
+            return Ok(false);
+        }
+
+        Ok(true)
+    }
+
+    // Opens the container whose bracket is at `span`, minding the depth limit.
+    fn enter(&mut self, span: Span) -> Result<Span> {
+        check_depth(self.lexer.options(), self.open.len())
+            .map_err(|kind| self.error(kind, span))?;
+
+        self.open.push(0);
+        self.keys.push(HashMap::new());
+        Ok(span)
+    }
+
+    fn leave(&mut self) {
+        self.open.pop();
+        self.keys.pop();
+    }
+
+    // Applies `ParseOptions::duplicate_keys` to `key` at `span`,
+    // giving `false` if its value is to be skipped.
+    fn unique_key(&mut self, key: &str, span: Span) -> Result<bool> {
+        let policy = self.lexer.options().duplicate_keys;
+        if let DuplicateKeys::LastWins | DuplicateKeys::KeepAll = policy {
+            return Ok(true);
+        }
+
+        let Some(keys) = self.keys.last_mut() else {
+            return Ok(true);
+        };
+
+        match keys.get(key) {
+            None => {
+                keys.insert(key.to_owned(), span);
+                Ok(true)
+            }
+
+            Some(&first) => match policy {
+                DuplicateKeys::Error => {
+                    let kind = ErrorKind::DuplicateKey {
+                        key: key.to_owned(),
+                        first,
+                    };
+
+                    Err(self.error(kind, span))
+                }
+
+                _ => Ok(false),
+            },
+        }
+    }
+
+    // Counts one more entry of the innermost container, starting at `span`.
+    fn count_entry(&mut self, span: Span) -> Result<()> {
+        if let Some(count) = self.open.last_mut() {
+            check_members(self.lexer.options(), *count)
+                .map_err(|kind| self.lexer.error(kind, span))?;
+            *count += 1;
+        }
+
+        Ok(())
+    }
+
+    /// Parses the next value into a `JsonValue`.
+    pub fn value(&mut self) -> Result<JsonValue> {
+        let value = match self.eof_next("a value")? {
+            Token::LeftBracket(span) => {
+                self.enter(span)?;
+                self.object()?
+            }
+
+            Token::LeftSquareBracket(span) => {
+                self.enter(span)?;
+                self.array()?
+            }
+
+            Token::String(string, _) => JsonValue::String(string.into_owned()),
+            Token::Int(num, _) => JsonValue::Number(Number::Int(num)),
+            Token::Float(num, _) => JsonValue::Number(Number::Float(num)),
+            Token::Bool(val, _) => JsonValue::Bool(val),
+            Token::Null(_) => JsonValue::Null,
+
+            token => return wrong_token!(self, token, "a value"),
+        };
+
+        Ok(value)
+    }
+
+    // Requires the `{` to be already consumed.
+    fn object(&mut self) -> Result<JsonValue> {
+        let keep_all = self.lexer.options().duplicate_keys == DuplicateKeys::KeepAll;
+        let mut map = Map::new();
+        let mut first = true;
+
+        while let Some((key, _)) = self.next_key(first)? {
+            first = false;
+
+            let value = self.value()?;
+            match keep_all {
+                true => map.append(key, value),
+                false => {
+                    map.insert(key, value);
+                }
+            }
+        }
+
+        // sorted once at the end rather than inserting each key at its place
+        if self.lexer.options().sorted_maps {
+            map.keep_sorted();
+        }
+
+        Ok(JsonValue::Object(map))
+    }
+
+    // Requires the `[` to be already consumed.
+    fn array(&mut self) -> Result<JsonValue> {
+        let mut vec = Vec::new();
+        let mut first = true;
+
+        while self.next_element(first)? {
+            first = false;
+
+            vec.push(self.value()?);
+        }
+
+        Ok(JsonValue::Array(vec))
+    }
+}
+
+/// What `Parser::parse_recovering` gives back.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Recovered {
+    /// The tree, with `null` where values couldn't be parsed.
+    pub value: JsonValue,
+
+    /// Spans of what stood in the input where `value` has such a `null`, in input order.
+    pub placeholders: Vec<Span>,
+
+    pub errors: Vec<Error>,
+}
+
+// State of `Parser::parse_recovering`.
+struct Recovery<'r, 'p> {
+    parser: &'r mut Parser<'p>,
+    errors: Vec<Error>,
+
+    // spans of the `null`s standing in for values that couldn't be parsed
+    placeholders: Vec<Span>,
+
+    // a token handed back to be read again by what comes next
+    pending: Option<Token<'p>>,
+
+    // set once a limit was hit, the input is over as far as recovery is concerned
+    fatal: bool,
+}
+
+// What `Recovery::fetch` came up with.
+enum Fetched<'p> {
+    Token(Token<'p>),
+
+    // a broken token was reported and skipped
+    Failed,
+
+    End,
+}
+
+impl<'p> Recovery<'_, 'p> {
+    fn run(mut self) -> Recovered {
+        let value = match self.fetch() {
+            Fetched::Token(token) => self.value(token),
+            Fetched::Failed => self.placeholder(),
+            Fetched::End => {
+                self.eof("a value");
+                self.placeholder()
+            }
+        };
+
+        // anything after the root value is reported once
+        if let Fetched::Token(token) = self.fetch() {
+            self.unexpected(&token, "end of input after the root value");
+        }
+
+        Recovered {
+            value,
+            placeholders: self.placeholders,
+            errors: self.errors,
+        }
+    }
+
+    fn fetch(&mut self) -> Fetched<'p> {
+        if let Some(token) = self.pending.take() {
+            return Fetched::Token(token);
+        }
+
+        if self.fatal {
+            return Fetched::End;
+        }
+
+        match self.parser.next() {
+            Ok(Some(token)) => Fetched::Token(token),
+            Ok(None) => Fetched::End,
+
+            Err(err) => {
+                if !err.kind().is_limit() {
+                    self.parser.lexer.recover(&err);
+                }
+
+                self.report(err);
+
+                match self.fatal {
+                    true => Fetched::End,
+                    false => Fetched::Failed,
+                }
+            }
+        }
+    }
+
+    // Records `err`, a limit error stops everything.
+    fn report(&mut self, err: Error) {
+        if self.fatal {
+            return;
+        }
+
+        self.fatal = err.kind().is_limit();
+        self.errors.push(err);
+    }
+
+    // A `null` standing in for a value that couldn't be parsed,
+    // where the error just reported is.
+    fn placeholder(&mut self) -> JsonValue {
+        let span = match self.errors.last() {
+            Some(err) => err.span(),
+            None => {
+                let end = self.parser.lexer.input().len();
+                Span::new(end, end)
+            }
+        };
+
+        self.placeholders.push(span);
+        JsonValue::Null
+    }
+
+    fn unexpected(&mut self, token: &Token, expected: &'static str) {
+        let kind = ErrorKind::UnexpectedToken {
+            found: describe(token),
+            expected,
+        };
+
+        let err = self.parser.error(kind, *token.span());
+        self.report(err);
+    }
+
+    fn eof(&mut self, expected: &'static str) {
+        let end = self.parser.lexer.input().len();
+        let err = self
+            .parser
+            .error(ErrorKind::UnexpectedEof(expected), Span::new(end, end));
+
+        self.report(err);
+    }
+
+    // Parses the value `token` starts, anything else is reported and left out.
+    fn value(&mut self, token: Token<'p>) -> JsonValue {
+        match token {
+            Token::LeftBracket(span) => match self.parser.enter(span) {
+                Ok(_) => self.object(),
+                Err(err) => {
+                    self.report(err);
+                    self.placeholder()
+                }
+            },
+
+            Token::LeftSquareBracket(span) => match self.parser.enter(span) {
+                Ok(_) => self.array(),
+                Err(err) => {
+                    self.report(err);
+                    self.placeholder()
+                }
+            },
+
+            Token::String(string, _) => JsonValue::String(string.into_owned()),
+            Token::Int(num, _) => JsonValue::Number(Number::Int(num)),
+            Token::Float(num, _) => JsonValue::Number(Number::Float(num)),
+            Token::Bool(val, _) => JsonValue::Bool(val),
+            Token::Null(_) => JsonValue::Null,
+
+            token => {
+                self.unexpected(&token, "a value");
+                self.placeholder()
+            }
+        }
+    }
+
+    // Where a value is missing, separators and brackets are handed back for what comes next.
+    fn missing_value(&mut self, token: Token<'p>) -> JsonValue {
+        self.unexpected(&token, "a value");
+
+        if let Token::Comma(_) | Token::RightBracket(_) | Token::RightSquareBracket(_) = token {
+            self.pending = Some(token);
+        }
+
+        self.placeholder()
+    }
+
+    // Requires the `{` to be already consumed.
+    fn object(&mut self) -> JsonValue {
+        let options = *self.parser.lexer.options();
+        let mut map = Map::new();
+        let mut first = true;
+
+        loop {
+            let (key, span) = match self.fetch() {
+                Fetched::Token(Token::RightBracket(_))
+                    if first || options.dialect.trailing_commas() =>
+                {
+                    break;
+                }
+
+                Fetched::Token(Token::String(key, span)) => (key.into_owned(), span),
+
+                Fetched::Token(token) => match ident_key(
+                    self.parser.lexer.dialect(),
+                    &token,
+                    self.parser.text(*token.span()),
+                ) {
+                    Some(key) => (key.into_owned(), *token.span()),
+                    None => {
+                        self.unexpected(&token, "a string key");
+                        self.pending = Some(token);
+
+                        match self.skip_entry() {
+                            true => continue,
+                            false => break,
+                        }
+                    }
+                },
+
+                Fetched::Failed => match self.skip_entry() {
+                    true => continue,
+                    false => break,
+                },
+
+                Fetched::End => {
+                    self.eof("a string key or `}`");
+                    break;
+                }
+            };
+
+            first = false;
+
+            if key.len() > options.max_key_len {
+                let err = self
+                    .parser
+                    .error(ErrorKind::KeyTooLong(options.max_key_len), span);
+                self.report(err);
+            }
+
+            if let Err(err) = self.parser.count_entry(span) {
+                self.report(err);
+            }
+
+            match self.fetch() {
+                Fetched::Token(Token::Colon(_)) => {}
+
+                // most likely a forgotten `:`
+                Fetched::Token(token) if starts_value(&token) => {
+                    self.unexpected(&token, "`:`");
+                    self.pending = Some(token);
+                }
+
+                Fetched::Token(token) => {
+                    self.unexpected(&token, "`:`");
+                    self.pending = Some(token);
+                    map.insert(key, self.placeholder());
+
+                    match self.skip_entry() {
+                        true => continue,
+                        false => break,
+                    }
+                }
+
+                Fetched::Failed => match self.skip_entry() {
+                    true => continue,
+                    false => break,
+                },
+
+                Fetched::End => {
+                    self.eof("`:`");
+                    map.insert(key, self.placeholder());
+                    break;
+                }
+            }
+
+            let value = match self.fetch() {
+                Fetched::Token(token) if starts_value(&token) => self.value(token),
+                Fetched::Token(token) => self.missing_value(token),
+                Fetched::Failed => self.placeholder(),
+                Fetched::End => {
+                    self.eof("a value");
+                    self.placeholder()
+                }
+            };
+
+            match self.parser.unique_key(&key, span) {
+                Ok(true) if options.duplicate_keys == DuplicateKeys::KeepAll => {
+                    map.append(key, value)
+                }
+
+                Ok(true) => {
+                    map.insert(key, value);
+                }
+
+                // the first value stays
+                Ok(false) => {}
+                Err(err) => self.report(err),
+            }
+
+            if !self.separator(true) {
+                break;
+            }
+        }
+
+        self.parser.leave();
+
+        if options.sorted_maps {
+            map.keep_sorted();
+        }
+
+        JsonValue::Object(map)
+    }
+
+    // Requires the `[` to be already consumed.
+    fn array(&mut self) -> JsonValue {
+        let trailing = self.parser.lexer.dialect().trailing_commas();
+        let mut vec = Vec::new();
+        let mut first = true;
+
+        loop {
+            let value = match self.fetch() {
+                Fetched::Token(Token::RightSquareBracket(_)) if first || trailing => break,
+
+                Fetched::Token(token) if starts_value(&token) => {
+                    if let Err(err) = self.parser.count_entry(*token.span()) {
+                        self.report(err);
+                    }
+
+                    self.value(token)
+                }
+
+                // a trailing comma gives no placeholder
+                Fetched::Token(token @ Token::RightSquareBracket(_)) => {
+                    self.unexpected(&token, "a value");
+                    break;
+                }
+
+                Fetched::Token(token) => self.missing_value(token),
+                Fetched::Failed => self.placeholder(),
+
+                Fetched::End => {
+                    self.eof(match first {
+                        true => "a value or `]`",
+                        false => "a value",
+                    });
+                    break;
+                }
+            };
+
+            first = false;
+            vec.push(value);
+
+            if !self.separator(false) {
+                break;
+            }
+        }
+
+        self.parser.leave();
+        JsonValue::Array(vec)
+    }
+
+    // Reads the `,` or closing bracket after an entry, giving whether more entries follow.
+    // Anything else in between is reported and skipped.
+    fn separator(&mut self, object: bool) -> bool {
+        let expected = match object {
+            true => "`,` or `}`",
+            false => "`,` or `]`",
+        };
+
+        loop {
+            match self.fetch() {
+                Fetched::Token(Token::Comma(_)) => return true,
+                Fetched::Token(Token::RightBracket(_)) if object => return false,
+                Fetched::Token(Token::RightSquareBracket(_)) if !object => return false,
+
+                // most likely a forgotten `,`
+                Fetched::Token(token)
+                    if starts_value(&token) || matches!(token, Token::Ident(..)) =>
+                {
+                    self.unexpected(&token, expected);
+                    self.pending = Some(token);
+                    return true;
+                }
+
+                // the wrong closing bracket still closes the container
+                Fetched::Token(token @ (Token::RightBracket(_) | Token::RightSquareBracket(_))) => {
+                    self.unexpected(&token, expected);
+                    return false;
+                }
+
+                Fetched::Token(token) => self.unexpected(&token, expected),
+                Fetched::Failed => {}
+
+                Fetched::End => {
+                    self.eof(expected);
+                    return false;
+                }
+            }
+        }
+    }
+
+    // Skips the rest of a broken entry, containers in it included,
+    // up to the `,` or closing bracket after it, giving whether more entries follow.
+    fn skip_entry(&mut self) -> bool {
+        let mut depth = 0;
+
+        loop {
+            match self.fetch() {
+                Fetched::Token(Token::LeftBracket(_) | Token::LeftSquareBracket(_)) => depth += 1,
+
+                Fetched::Token(Token::RightBracket(_) | Token::RightSquareBracket(_)) => {
+                    match depth {
+                        0 => return false,
+                        _ => depth -= 1,
+                    }
+                }
+
+                Fetched::Token(Token::Comma(_)) if depth == 0 => return true,
+                Fetched::Token(_) | Fetched::Failed => {}
+
+                Fetched::End => {
+                    self.eof("`,` or a closing bracket");
+                    return false;
+                }
+            }
+        }
+    }
+}
+
+// --- HELPER FUNCTIONS --- //
+
+// Checks a container can be opened inside `depth` others,
+// the span of the bracket is up to the caller.
+pub(crate) fn check_depth(options: &ParseOptions, depth: usize) -> KindResult<()> {
+    let max = options.max_depth;
+
+    match depth >= max {
+        true => Err(ErrorKind::DepthLimitExceeded(max)),
+        false => Ok(()),
+    }
+}
+
+// Checks there is room for one more entry after `count`,
+// the span of the new one is up to the caller.
+pub(crate) fn check_members(options: &ParseOptions, count: usize) -> KindResult<()> {
+    let max = options.max_members;
+
+    match count >= max {
+        true => Err(ErrorKind::TooManyMembers(max)),
+        false => Ok(()),
+    }
+}
+
+// The key `token` spells if it can be an unquoted JSON5 key, `text` being its text,
+// which includes the names of literals like `null` or `NaN`.
+pub(crate) fn ident_key<'t>(
+    dialect: Dialect,
+    token: &Token<'t>,
+    text: &str,
+) -> Option<Cow<'t, str>> {
+    match *token {
+        Token::Ident(ref name, _) => Some(name.clone()),
+
+        Token::Bool(..) | Token::Null(_) | Token::Float(..)
+            if dialect == Dialect::Json5 && text.starts_with(char::is_alphabetic) =>
+        {
+            Some(Cow::Owned(text.to_owned()))
+        }
+
+        _ => None,
+    }
+}
+
+// Whether `token` can start a value.
+fn starts_value(token: &Token) -> bool {
+    matches!(
+        token,
+        Token::LeftBracket(_)
+            | Token::LeftSquareBracket(_)
+            | Token::String(..)
+            | Token::Int(..)
+            | Token::Float(..)
+            | Token::Bool(..)
+            | Token::Null(_)
+    )
+}
+
+// Human readable name of a token for error messages.
+pub(crate) fn describe(token: &Token) -> &'static str {
+    match token {
+        Token::LeftBracket(_) => "`{`",
+        Token::RightBracket(_) => "`}`",
+        Token::LeftSquareBracket(_) => "`[`",
+        Token::RightSquareBracket(_) => "`]`",
+        Token::Colon(_) => "`:`",
+        Token::Comma(_) => "`,`",
+        Token::String(..) => "a string",
+        Token::Int(..) | Token::Float(..) => "a number",
+        Token::Bool(..) => "a bool",
+        Token::Null(_) => "`null`",
+        Token::Ident(..) => "an identifier",
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse(input: &str) -> Result<JsonValue> {
+        Parser::new(input).parse()
+    }
+
+    fn member<'v>(value: &'v JsonValue, key: &str) -> &'v JsonValue {
+        match value {
+            JsonValue::Object(map) => map.get(key).expect(key),
+            value => panic!("expected an object, got {value:?}"),
+        }
+    }
+
+    #[test]
+    fn values_in_containers() {
+        let value = parse("{\"a\":[\"s\",\"t\",{}],\"b\":{\"c\":{}},\"d\":[]}").unwrap();
+
+        assert_eq!(
+            member(&value, "a"),
+            &JsonValue::Array(vec![
+                JsonValue::String("s".to_string()),
+                JsonValue::String("t".to_string()),
+                JsonValue::Object(Map::new()),
+            ])
+        );
+        assert_eq!(member(&value, "d"), &JsonValue::Array(Vec::new()));
+        assert_eq!(
+            member(member(&value, "b"), "c"),
+            &JsonValue::Object(Map::new())
+        );
+    }
+
+    #[test]
+    fn keywords_end_where_they_should() {
+        let value = parse("{\"a\":[true,null],\"b\":null}").unwrap();
+
+        assert_eq!(
+            member(&value, "a"),
+            &JsonValue::Array(vec![JsonValue::Bool(true), JsonValue::Null])
+        );
+        assert_eq!(member(&value, "b"), &JsonValue::Null);
+    }
+
+    #[test]
+    fn deep_nesting() {
+        let input = format!("{{\"a\":{}\"x\"{}}}", "[".repeat(50), "]".repeat(50));
+        let mut value = member(&parse(&input).unwrap(), "a").clone();
+
+        for _ in 0..50 {
+            value = match value {
+                JsonValue::Array(mut vec) if vec.len() == 1 => vec.remove(0),
+                value => panic!("expected an array of one value, got {value:?}"),
+            };
+        }
+
+        assert_eq!(value, JsonValue::String("x".to_string()));
+    }
+
+    #[test]
+    fn broken_documents() {
+        for input in [
+            "{\"a\" \"b\"}",
+            "{\"a\":}",
+            "{\"a\":[\"b\" \"c\"]}",
+            "{\"a\":\"b\",}",
+            "{\"a\":[\"b\",]}",
+            "{[]:\"b\"}",
+            "{\"a\":\"b\"",
+            "{\"a\":[",
+            "{\"a\":\"b\"}}",
+            "",
+        ] {
+            assert!(parse(input).is_err(), "{input:?}");
+        }
+    }
 }
@@ -0,0 +1,129 @@
+/*
+    Resumable parser fed with bytes as they arrive.
+*/
+
+use crate::error::Error;
+use crate::event::{Grammar, SpannedEvent};
+use crate::lexer::Dialect;
+use crate::options::ParseOptions;
+use crate::reader::TextBuffer;
+
+type Result<T> = std::result::Result<T, Error>;
+
+/// What `PushParser::next_event` came up with.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Progress {
+    Event(SpannedEvent<'static>),
+
+    /// The input fed so far is used up, `feed` more or `finish`.
+    NeedMore,
+
+    /// The whole document was read.
+    Done,
+}
+
+/// A parser that never blocks: bytes are handed over with `feed`
+/// whenever they arrive, and `next_event` drains what can be parsed out of them.
+///
+/// Only the unfinished token and the stack of open containers are kept between calls.
+#[derive(Debug)]
+pub struct PushParser {
+    buffer: TextBuffer,
+    grammar: Grammar,
+
+    // set by `finish`
+    finished: bool,
+
+    // given out again once an error was hit
+    error: Option<Error>,
+}
+
+impl PushParser {
+    /// Creates a new `PushParser` with the default `ParseOptions`.
+    pub fn new() -> Self {
+        Self::with_options(ParseOptions::new())
+    }
+
+    /// Creates a new `PushParser` accepting the given `Dialect`.
+    pub fn with_dialect(dialect: Dialect) -> Self {
+        Self::with_options(ParseOptions::new().dialect(dialect))
+    }
+
+    /// Creates a new `PushParser` enforcing the limits of `options`.
+    pub fn with_options(options: ParseOptions) -> Self {
+        Self {
+            buffer: TextBuffer::new(options),
+            grammar: Grammar::new(options),
+            finished: false,
+            error: None,
+        }
+    }
+
+    /// Hands over the next bytes of input,
+    /// they may end anywhere, even in the middle of a character.
+    ///
+    /// # Panics
+    /// If called after `finish`.
+    pub fn feed(&mut self, bytes: &[u8]) -> Result<()> {
+        assert!(!self.finished, "`PushParser::feed` called after `finish`");
+
+        self.buffer.push(bytes).map_err(|err| self.fail(err))
+    }
+
+    /// Marks the end of input, the events left can still be drained.
+    pub fn finish(&mut self) -> Result<()> {
+        self.finished = true;
+
+        self.buffer.end().map_err(|err| self.fail(err))
+    }
+
+    /// Number of containers open after the last event.
+    pub fn depth(&self) -> usize {
+        self.grammar.depth()
+    }
+
+    /// Parses the next event out of the input fed so far.
+    /// Once an error is returned every later call returns it again.
+    pub fn next_event(&mut self) -> Result<Progress> {
+        if let Some(ref err) = self.error {
+            return Err(err.clone());
+        }
+
+        loop {
+            let token = match self.buffer.lex(self.finished) {
+                Ok(Some(token)) => token,
+                Ok(None) if !self.finished => return Ok(Progress::NeedMore),
+
+                Ok(None) => {
+                    return match self.grammar.finish() {
+                        Ok(()) => Ok(Progress::Done),
+                        Err(kind) => Err(self.fail(self.buffer.error_at_end(kind))),
+                    };
+                }
+
+                Err(err) => return Err(self.fail(err)),
+            };
+
+            let span = *token.span();
+            let text = self.buffer.slice(span);
+
+            match self.grammar.token(token, text) {
+                Ok(Some(event)) => return Ok(Progress::Event(event)),
+                Ok(None) => continue,
+                Err(kind) => return Err(self.fail(self.buffer.error(kind, span))),
+            }
+        }
+    }
+
+    // Remembers `err` so it is returned from then on.
+    fn fail(&mut self, err: Error) -> Error {
+        self.error = Some(err.clone());
+        err
+    }
+}
+
+impl Default for PushParser {
+    fn default() -> Self {
+        Self::new()
+    }
+}
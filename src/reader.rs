@@ -0,0 +1,435 @@
+/*
+    Lexing JSON that arrives in chunks, read from an `io::Read` or pushed by hand.
+*/
+
+use std::io::{self, Read};
+
+use crate::error::{Error, ErrorKind};
+use crate::lexer::{Dialect, Lexer, Span, Token};
+use crate::options::ParseOptions;
+
+type Result<T> = std::result::Result<T, Error>;
+
+const CHUNK_SIZE: usize = 64 * 1024;
+
+/// Lexes JSON read from an `io::Read` as it goes,
+/// the tokens own their text since the input doesn't stay around.
+///
+/// Only the text from the start of the current token on is kept,
+/// so memory use is bounded by the largest token plus a chunk
+/// rather than by the size of the document.
+/// Spans are byte offsets from the start of the stream.
+pub struct ReadLexer<R> {
+    reader: R,
+    buffer: TextBuffer,
+
+    // scratch space the reader writes into
+    chunk: Vec<u8>,
+
+    // set once the reader has nothing more to give
+    eof: bool,
+
+    // set once the input ran out or an error was returned
+    done: bool,
+}
+
+impl<R: Read> ReadLexer<R> {
+    /// Creates a new `ReadLexer` over `reader` with the default `ParseOptions`.
+    pub fn new(reader: R) -> Self {
+        Self::with_options(reader, ParseOptions::new())
+    }
+
+    /// Creates a new `ReadLexer` over `reader` accepting the given `Dialect`.
+    pub fn with_dialect(reader: R, dialect: Dialect) -> Self {
+        Self::with_options(reader, ParseOptions::new().dialect(dialect))
+    }
+
+    /// Creates a new `ReadLexer` over `reader`,
+    /// enforcing the input, token and string limits of `options`.
+    pub fn with_options(reader: R, options: ParseOptions) -> Self {
+        Self {
+            reader,
+            buffer: TextBuffer::new(options),
+            chunk: vec![0; CHUNK_SIZE],
+            eof: false,
+            done: false,
+        }
+    }
+
+    /// Sets how many bytes are asked of the reader at once, 64 KiB by default.
+    pub fn set_chunk_size(&mut self, size: usize) {
+        self.chunk.resize(size.max(1), 0);
+    }
+
+    pub fn dialect(&self) -> Dialect {
+        self.buffer.options.dialect
+    }
+
+    fn lex_token(&mut self) -> Result<Option<Token<'static>>> {
+        loop {
+            match self.buffer.lex(self.eof)? {
+                None if !self.eof => self.fill()?,
+                token => return Ok(token),
+            }
+        }
+    }
+
+    // Reads the next chunk into the buffer.
+    fn fill(&mut self) -> Result<()> {
+        let read = loop {
+            match self.reader.read(&mut self.chunk) {
+                Ok(read) => break read,
+                Err(err) if err.kind() == io::ErrorKind::Interrupted => continue,
+                Err(err) => return Err(self.buffer.error_at_end(ErrorKind::Io(err.to_string()))),
+            }
+        };
+
+        match read {
+            0 => {
+                self.eof = true;
+                self.buffer.end()
+            }
+
+            read => self.buffer.push(&self.chunk[..read]),
+        }
+    }
+}
+
+impl<R: Read> Iterator for ReadLexer<R> {
+    type Item = Result<Token<'static>>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.done {
+            return None;
+        }
+
+        let result = self.lex_token();
+
+        if !matches!(result, Ok(Some(_))) {
+            self.done = true;
+        }
+
+        result.transpose()
+    }
+}
+
+/// Input that arrives in pieces, decoded and lexed as far as it goes.
+///
+/// Keeps the text from the start of the next token on,
+/// along with where that text starts in the whole input.
+#[derive(Debug)]
+pub(crate) struct TextBuffer {
+    options: ParseOptions,
+
+    // tokens lexed so far, for `ParseOptions::max_tokens`
+    count: usize,
+
+    // decoded input, everything before `pos` is lexed already
+    text: String,
+    pos: usize,
+
+    // how far into `text` the string or comment at `pos` was looked through
+    // without finding its end, so each push only looks at what's new
+    scanned: usize,
+
+    // the fewest bytes the string at `pos` decodes to up to `scanned`,
+    // for `ParseOptions::max_string_len` to hold before the string is closed
+    decoded: usize,
+
+    // bytes pushed but not decoded, an incomplete character at most
+    bytes: Vec<u8>,
+
+    // where `text` starts in the whole input, lines and columns start at 1
+    base: usize,
+    line: usize,
+    column: usize,
+}
+
+impl TextBuffer {
+    pub(crate) fn new(options: ParseOptions) -> Self {
+        Self {
+            options,
+            count: 0,
+            text: String::new(),
+            pos: 0,
+            scanned: 0,
+            decoded: 0,
+            bytes: Vec::new(),
+            base: 0,
+            line: 1,
+            column: 1,
+        }
+    }
+
+    /// Adds the next bytes of input, which may end in the middle of a character.
+    pub(crate) fn push(&mut self, bytes: &[u8]) -> Result<()> {
+        self.compact();
+
+        let max = self.options.max_input_len;
+        let len = self.base + self.text.len() + self.bytes.len() + bytes.len();
+        if len > max {
+            // like the `Lexer`, the error covers what goes over the limit
+            return Err(self.error(ErrorKind::InputTooLarge(max), Span::new(max, len)));
+        }
+
+        self.bytes.extend_from_slice(bytes);
+
+        // an incomplete character at the end waits for the next push
+        let (valid, invalid) = match std::str::from_utf8(&self.bytes) {
+            Ok(text) => (text.len(), false),
+            Err(err) => (err.valid_up_to(), err.error_len().is_some()),
+        };
+
+        let decoded = std::str::from_utf8(&self.bytes[..valid]).expect("validated above");
+        self.text.push_str(decoded);
+        self.bytes.drain(..valid);
+
+        match invalid {
+            true => Err(self.error_at_end(ErrorKind::InvalidUtf8)),
+            false => Ok(()),
+        }
+    }
+
+    /// Checks the input didn't end in the middle of a character.
+    pub(crate) fn end(&self) -> Result<()> {
+        match self.bytes.is_empty() {
+            true => Ok(()),
+            false => Err(self.error_at_end(ErrorKind::InvalidUtf8)),
+        }
+    }
+
+    /// Lexes the next token, with its span counted from the start of the whole input.
+    /// Gives `None` when the buffer runs out: the input is over if `eof`,
+    /// otherwise a token might still be cut off and more has to be pushed.
+    pub(crate) fn lex(&mut self, eof: bool) -> Result<Option<Token<'static>>> {
+        // the `Lexer` only sees a string once it is closed, rather than once per push
+        if !eof && !self.ready() {
+            return Ok(None);
+        }
+
+        let rest = &self.text[self.pos..];
+
+        // the whole input and token count are checked here rather than on a slice
+        let options = ParseOptions {
+            max_tokens: usize::MAX,
+            max_input_len: usize::MAX,
+            ..self.options
+        };
+        let result = Lexer::with_options(rest, options).next().transpose();
+
+        // anything reaching the end of the buffer might go on in the next push,
+        // a number or a literal just as well as an unterminated string,
+        // only brackets, separators and strings end by themselves
+        let at_end = match result {
+            Ok(Some(
+                Token::Int(_, span)
+                | Token::Float(_, span)
+                | Token::Bool(_, span)
+                | Token::Null(span)
+                | Token::Ident(_, span),
+            )) => span.end() == rest.len(),
+
+            Ok(Some(_)) => false,
+            Ok(None) => true,
+            Err(ref err) => err.span().end() >= rest.len(),
+        };
+
+        if at_end && !eof {
+            return Ok(None);
+        }
+
+        match result {
+            Ok(Some(token)) => {
+                let token = token.into_owned().shifted(self.pos);
+                self.pos = token.span().end();
+
+                let token = token.shifted(self.base);
+                let max = self.options.max_tokens;
+
+                self.count += 1;
+                if self.count > max {
+                    return Err(self.error(ErrorKind::TooManyTokens(max), *token.span()));
+                }
+
+                Ok(Some(token))
+            }
+
+            Ok(None) => Ok(None),
+            Err(err) => {
+                let (start, end) = err.span().as_tuple();
+                let span = Span::new(self.pos + start, self.pos + end);
+
+                Err(self.error(err.kind().clone(), span.shifted(self.base)))
+            }
+        }
+    }
+
+    /// Text of the whole input under `span` if it is still buffered,
+    /// the last token lexed always is.
+    pub(crate) fn slice(&self, span: Span) -> &str {
+        let (start, end) = span.as_tuple();
+
+        self.text
+            .get(start - self.base..end - self.base)
+            .unwrap_or_default()
+    }
+
+    /// Creates an error at `span` of the whole input, which has to be buffered.
+    pub(crate) fn error(&self, kind: ErrorKind, span: Span) -> Error {
+        let (start, end) = span.as_tuple();
+        let local = Span::new(start - self.base, end - self.base);
+
+        Error::new(kind, local, &self.text).offset_by(self.base, self.line, self.column)
+    }
+
+    /// Creates an error right after the decoded text.
+    pub(crate) fn error_at_end(&self, kind: ErrorKind) -> Error {
+        let end = self.base + self.text.len();
+
+        self.error(kind, Span::new(end, end))
+    }
+
+    // Skips the whitespace and complete comments at `pos`, giving whether
+    // the token after them can be lexed as far as the buffer goes, a string has to be closed.
+    fn ready(&mut self) -> bool {
+        let dialect = self.options.dialect;
+
+        loop {
+            let Some(ch) = self.text[self.pos..].chars().next() else {
+                return false;
+            };
+
+            match ch {
+                ch if dialect.is_whitespace(ch) => self.pos += ch.len_utf8(),
+
+                '"' => return self.closed(b'"'),
+                '\'' if dialect == Dialect::Json5 => return self.closed(b'\''),
+
+                '/' if dialect.comments() => match self.text.as_bytes().get(self.pos + 1) {
+                    Some(b'/' | b'*') => match self.comment_end() {
+                        Some(end) => self.pos = end,
+                        None => return false,
+                    },
+
+                    // a `/` starting no comment is left for the `Lexer` to report
+                    Some(_) => return true,
+                    None => return false,
+                },
+
+                _ => return true,
+            }
+        }
+    }
+
+    // Whether the string at `pos` is closed by a `quote` or already too long,
+    // either way the `Lexer` can take it from there. Looks on from where the last look stopped.
+    fn closed(&mut self, quote: u8) -> bool {
+        let bytes = self.text.as_bytes();
+
+        if self.scanned <= self.pos {
+            self.scanned = self.pos + 1;
+            self.decoded = 0;
+        }
+
+        while self.scanned < bytes.len() {
+            // the `Lexer` stops at the first character past the limit
+            if self.decoded > self.options.max_string_len {
+                return true;
+            }
+
+            let (len, decoded) = match bytes[self.scanned] {
+                byte if byte == quote => return true,
+
+                // an escape cut off at the end of the buffer waits for the next push
+                b'\\' => match escape(&bytes[self.scanned..]) {
+                    Some(escape) => escape,
+                    None => return false,
+                },
+
+                _ => (1, 1),
+            };
+
+            self.scanned += len;
+            self.decoded += decoded;
+        }
+
+        false
+    }
+
+    // Where the comment at `pos` ends, a line comment right before its `\n`,
+    // looking on from where the last look stopped.
+    fn comment_end(&mut self) -> Option<usize> {
+        let bytes = self.text.as_bytes();
+        let from = self.scanned.max(self.pos + 2);
+
+        let end = match bytes[self.pos + 1] {
+            b'/' => bytes[from..].iter().position(|&byte| byte == b'\n'),
+            _ => bytes[from..]
+                .windows(2)
+                .position(|pair| pair == b"*/")
+                .map(|index| index + 2),
+        };
+
+        if end.is_none() {
+            // a `*` at the very end might start the `*/`
+            self.scanned = bytes.len().saturating_sub(1).max(self.pos + 2);
+        }
+
+        end.map(|index| from + index)
+    }
+
+    // Drops the text already lexed, keeping track of where the rest starts.
+    fn compact(&mut self) {
+        let consumed = &self.text[..self.pos];
+
+        match consumed.rfind('\n') {
+            Some(index) => {
+                self.line += consumed.matches('\n').count();
+                self.column = consumed[index + 1..].chars().count() + 1;
+            }
+
+            None => self.column += consumed.chars().count(),
+        }
+
+        self.base += self.pos;
+        self.text.drain(..self.pos);
+        self.scanned = self.scanned.saturating_sub(self.pos);
+        self.pos = 0;
+    }
+}
+
+// --- HELPER FUNCTIONS --- //
+
+// How many bytes the escape at the start of `bytes` takes and the fewest it decodes to,
+// `None` if `bytes` end before it does. Bytes of a character escaped whole count as
+// they come, a malformed escape is only skipped as far as the `Lexer` reports it.
+fn escape(bytes: &[u8]) -> Option<(usize, usize)> {
+    let hex = |count: usize| {
+        let digits = bytes[2..]
+            .iter()
+            .take(count)
+            .take_while(|byte| byte.is_ascii_hexdigit());
+        let found = digits.count();
+
+        match found < count && 2 + found == bytes.len() {
+            true => None,
+            false => Some((2 + found, 1)),
+        }
+    };
+
+    match *bytes.get(1)? {
+        b'u' => hex(4),
+        b'x' => hex(2),
+
+        // line continuations of JSON5 decode to nothing
+        b'\n' => Some((2, 0)),
+        b'\r' => match *bytes.get(2)? {
+            b'\n' => Some((3, 0)),
+            _ => Some((2, 0)),
+        },
+        // U+2028 and U+2029, the buffer only holds whole characters
+        0xE2 if matches!(bytes[2..4], [0x80, 0xA8 | 0xA9]) => Some((4, 0)),
+
+        _ => Some((2, 1)),
+    }
+}
@@ -0,0 +1,477 @@
+/*
+    Writing JSON out, straight into a `fmt::Write` or `io::Write` sink.
+*/
+
+use std::fmt::{self, Write};
+use std::io;
+
+use crate::error::SerializeError;
+use crate::layout::{self, Layout, Piece};
+use crate::options::SerializeOptions;
+use crate::traits::JsonSerialize;
+
+/// Writes JSON tokens into a `fmt::Write` sink,
+/// placing the `,` and `:` separators and the layout of `SerializeOptions` by itself.
+///
+/// Apart from the containers open, the only state kept is whether the next item
+/// is the first one in the current object or array. Nothing is buffered
+/// unless keys are sorted, then the members of each object are held back until its end,
+/// or lines are fit to `SerializeOptions::max_width`, then the whole value is.
+pub struct Serializer<'w> {
+    out: &'w mut dyn Write,
+    options: SerializeOptions,
+    first: bool,
+
+    // containers currently open
+    depth: usize,
+
+    // keys and written values of the members of every object open while keys are sorted,
+    // innermost last, the text of a member's value goes to the last one
+    held: Vec<Vec<(String, String)>>,
+
+    // what is written in the smart layout, which takes the place of everything above
+    layout: Option<Layout>,
+
+    // why the `Serializer` itself refused a value, `fmt::Error` carries nothing
+    error: Option<SerializeError>,
+}
+
+impl<'w> Serializer<'w> {
+    /// Creates a new `Serializer` writing compact JSON into `out`.
+    pub fn new(out: &'w mut dyn Write) -> Self {
+        Self::with_options(out, SerializeOptions::compact())
+    }
+
+    /// Creates a new `Serializer` writing into `out` as `options` say.
+    pub fn with_options(out: &'w mut dyn Write, options: SerializeOptions) -> Self {
+        let options = match options.canonical {
+            true => SerializeOptions::canonical(),
+            false => options,
+        };
+
+        Self {
+            out,
+            first: true,
+            depth: 0,
+            held: Vec::new(),
+            layout: match options.max_width.is_some() && options.indent.is_some() {
+                true => Some(Layout::default()),
+                false => None,
+            },
+            options,
+            error: None,
+        }
+    }
+
+    pub fn options(&self) -> &SerializeOptions {
+        &self.options
+    }
+
+    /// Why the last `fmt::Error` was returned, if it was the `Serializer` refusing a value
+    /// rather than the sink failing.
+    pub fn error(&self) -> Option<&SerializeError> {
+        self.error.as_ref()
+    }
+
+    pub fn begin_object(&mut self) -> fmt::Result {
+        if let Some(layout) = &mut self.layout {
+            layout.open(true);
+            return Ok(());
+        }
+
+        self.open('{')?;
+
+        if self.options.sort_keys {
+            self.held.push(Vec::new());
+        }
+
+        Ok(())
+    }
+
+    /// Writes the key of the next member of an object along with its `:`,
+    /// the value has to be written right after.
+    pub fn key(&mut self, key: &str) -> fmt::Result {
+        if let Some(layout) = &mut self.layout {
+            layout.key(key);
+            return Ok(());
+        }
+
+        if let Some(members) = self.held.last_mut() {
+            members.push((key.to_owned(), String::new()));
+            self.first = false;
+
+            return Ok(());
+        }
+
+        self.entry()?;
+        self.write_key(key)
+    }
+
+    pub fn end_object(&mut self) -> fmt::Result {
+        if self.layout.is_some() {
+            return self.close_layout();
+        }
+
+        // the held back members go out in key order, stable for duplicates
+        if let Some(mut members) = self.held.pop() {
+            match self.options.canonical {
+                true => members.sort_by(|(a, _), (b, _)| a.encode_utf16().cmp(b.encode_utf16())),
+                false => members.sort_by(|(a, _), (b, _)| a.cmp(b)),
+            }
+
+            self.first = true;
+            for (key, value) in members {
+                self.entry()?;
+                self.write_key(&key)?;
+                sink(self.out, &mut self.held).write_str(&value)?;
+                self.first = false;
+            }
+        }
+
+        self.close('}')
+    }
+
+    pub fn begin_array(&mut self) -> fmt::Result {
+        if let Some(layout) = &mut self.layout {
+            layout.open(false);
+            return Ok(());
+        }
+
+        self.open('[')
+    }
+
+    /// Prepares for the next element of an array,
+    /// the value has to be written right after.
+    pub fn element(&mut self) -> fmt::Result {
+        match self.layout {
+            Some(_) => Ok(()),
+            None => self.entry(),
+        }
+    }
+
+    pub fn end_array(&mut self) -> fmt::Result {
+        match self.layout {
+            Some(_) => self.close_layout(),
+            None => self.close(']'),
+        }
+    }
+
+    pub fn write_str(&mut self, string: &str) -> fmt::Result {
+        self.scalar(|out, options| write_escaped(out, string, options))
+    }
+
+    /// Canonical output has doubles only, integers that aren't exactly one fail there.
+    pub fn write_i64(&mut self, num: i64) -> fmt::Result {
+        if self.options.canonical {
+            return self.write_exact(num.into());
+        }
+
+        self.scalar(|out, _| write!(out, "{num}"))
+    }
+
+    /// Canonical output has doubles only, integers that aren't exactly one fail there.
+    pub fn write_u64(&mut self, num: u64) -> fmt::Result {
+        if self.options.canonical {
+            return self.write_exact(num.into());
+        }
+
+        self.scalar(|out, _| write!(out, "{num}"))
+    }
+
+    /// Non finite floats have no JSON representation and are written as `null`,
+    /// or fail in canonical output which has no room for them.
+    pub fn write_f64(&mut self, num: f64) -> fmt::Result {
+        match (num.is_finite(), self.options.canonical) {
+            (false, true) => self.refuse(SerializeError::NonFiniteFloat(num)),
+            (false, false) => self.write_null(),
+            (true, true) => self.scalar(|out, _| write_ecmascript(out, num)),
+
+            // `Debug` keeps the `.0` of whole numbers
+            (true, false) => self.scalar(|out, _| write!(out, "{num:?}")),
+        }
+    }
+
+    pub fn write_bool(&mut self, val: bool) -> fmt::Result {
+        self.scalar(|out, _| out.write_str(if val { "true" } else { "false" }))
+    }
+
+    pub fn write_null(&mut self) -> fmt::Result {
+        self.scalar(|out, _| out.write_str("null"))
+    }
+
+    // Writes an integer as the double it is exactly, failing if there is none.
+    fn write_exact(&mut self, num: i128) -> fmt::Result {
+        let float = num as f64;
+
+        match float as i128 == num {
+            true => self.write_f64(float),
+            false => self.refuse(SerializeError::InexactInteger(num)),
+        }
+    }
+
+    // Fails with `err` as the reason.
+    fn refuse(&mut self, err: SerializeError) -> fmt::Result {
+        self.error = Some(err);
+        Err(fmt::Error)
+    }
+
+    // Writes a scalar where it goes, held back in the smart layout while a container is open.
+    fn scalar(
+        &mut self,
+        write: impl FnOnce(&mut dyn Write, &SerializeOptions) -> fmt::Result,
+    ) -> fmt::Result {
+        self.first = false;
+
+        if let Some(layout) = &mut self.layout
+            && layout.is_open()
+        {
+            let mut text = String::new();
+            write(&mut text, &self.options)?;
+            layout.push(Piece::Scalar(text));
+
+            return Ok(());
+        }
+
+        write(sink(self.out, &mut self.held), &self.options)
+    }
+
+    // Closes a container in the smart layout, laying out the root value once it ends.
+    fn close_layout(&mut self) -> fmt::Result {
+        let Some(layout) = &mut self.layout else {
+            return Ok(());
+        };
+
+        match layout.close(&self.options) {
+            Some(piece) => layout::render(self.out, &piece, &self.options),
+            None => Ok(()),
+        }
+    }
+
+    // Writes the opening bracket of a container.
+    fn open(&mut self, bracket: char) -> fmt::Result {
+        self.first = true;
+        self.depth += 1;
+        sink(self.out, &mut self.held).write_char(bracket)
+    }
+
+    // Writes the closing bracket of a container, on a line of its own unless it is empty.
+    fn close(&mut self, bracket: char) -> fmt::Result {
+        self.depth -= 1;
+
+        if !self.first {
+            self.newline()?;
+        }
+
+        self.first = false;
+        sink(self.out, &mut self.held).write_char(bracket)
+    }
+
+    // Writes what comes before an entry, its `,` and the start of its line.
+    fn entry(&mut self) -> fmt::Result {
+        if !self.first {
+            sink(self.out, &mut self.held).write_char(',')?;
+        }
+
+        self.newline()
+    }
+
+    fn write_key(&mut self, key: &str) -> fmt::Result {
+        let options = &self.options;
+        let out = sink(self.out, &mut self.held);
+
+        write_escaped(out, key, options)?;
+        out.write_str(if options.space_after_colon { ": " } else { ":" })
+    }
+
+    // Starts a new line indented to the current depth, nothing on a single line.
+    fn newline(&mut self) -> fmt::Result {
+        let Some(ref indent) = self.options.indent else {
+            return Ok(());
+        };
+
+        let out = sink(self.out, &mut self.held);
+        out.write_str(&self.options.newline)?;
+        (0..self.depth).try_for_each(|_| out.write_str(indent))
+    }
+}
+
+/// Writes `value` as compact JSON into a `String`.
+///
+/// # Panics
+/// If the `JsonSerialize` implementation returns an error, `to_string_with` gives it back instead.
+pub fn to_string<T: JsonSerialize + ?Sized>(value: &T) -> String {
+    // compact output has room for everything and writing into a `String` can't fail
+    to_string_with(value, &SerializeOptions::compact())
+        .expect("a `JsonSerialize` implementation returned an error")
+}
+
+/// Writes `value` as JSON into a `String` as `options` say, failing for values the options
+/// have no room for, like non finite floats in canonical output, or if a `JsonSerialize` impl does.
+pub fn to_string_with<T: JsonSerialize + ?Sized>(
+    value: &T,
+    options: &SerializeOptions,
+) -> Result<String, SerializeError> {
+    let mut string = String::new();
+    let mut ser = Serializer::with_options(&mut string, options.clone());
+
+    match value.serialize(&mut ser) {
+        Ok(()) => Ok(string),
+        Err(_) => Err(ser.error.unwrap_or(SerializeError::Write)),
+    }
+}
+
+/// Writes `value` in the canonical form of RFC 8785, see `SerializeOptions::canonical`.
+pub fn to_canonical_vec<T: JsonSerialize + ?Sized>(value: &T) -> Result<Vec<u8>, SerializeError> {
+    to_string_with(value, &SerializeOptions::canonical()).map(String::into_bytes)
+}
+
+/// Writes `value` as compact JSON into an `io::Write` sink.
+pub fn to_writer<W: io::Write, T: JsonSerialize + ?Sized>(writer: W, value: &T) -> io::Result<()> {
+    to_writer_with(writer, value, &SerializeOptions::compact())
+}
+
+/// Writes `value` as JSON into an `io::Write` sink as `options` say.
+pub fn to_writer_with<W: io::Write, T: JsonSerialize + ?Sized>(
+    writer: W,
+    value: &T,
+    options: &SerializeOptions,
+) -> io::Result<()> {
+    let mut adapter = IoAdapter {
+        inner: writer,
+        error: None,
+    };
+
+    let mut ser = Serializer::with_options(&mut adapter, options.clone());
+    let refused = match value.serialize(&mut ser) {
+        Ok(()) => return Ok(()),
+        Err(_) => ser.error.take(),
+    };
+
+    Err(match (adapter.error, refused) {
+        (Some(err), _) => err,
+        (None, Some(err)) => io::Error::new(io::ErrorKind::InvalidData, err),
+        (None, None) => io::Error::other(SerializeError::Write),
+    })
+}
+
+// Lets the `Serializer` write into an `io::Write`,
+// `fmt::Error` carries nothing so the actual error is stashed here.
+struct IoAdapter<W> {
+    inner: W,
+    error: Option<io::Error>,
+}
+
+impl<W: io::Write> Write for IoAdapter<W> {
+    fn write_str(&mut self, string: &str) -> fmt::Result {
+        self.inner.write_all(string.as_bytes()).map_err(|err| {
+            self.error = Some(err);
+            fmt::Error
+        })
+    }
+}
+
+// --- HELPER FUNCTIONS --- //
+
+// Where text goes, the value of the last member held back if keys are sorted.
+fn sink<'s>(out: &'s mut dyn Write, held: &'s mut [Vec<(String, String)>]) -> &'s mut dyn Write {
+    match held.last_mut().and_then(|members| members.last_mut()) {
+        Some((_, value)) => value,
+        None => out,
+    }
+}
+
+// Writes `string` quoted, escaping what RFC 8259 requires
+// along with what `SerializeOptions::ascii_only` and `SerializeOptions::escape_html` ask for.
+pub(crate) fn write_escaped(
+    out: &mut dyn Write,
+    string: &str,
+    options: &SerializeOptions,
+) -> fmt::Result {
+    out.write_char('"')?;
+
+    // unescaped runs are written in one go
+    let mut start = 0;
+    for (index, ch) in string.char_indices() {
+        let escape = match ch {
+            '"' => "\\\"",
+            '\\' => "\\\\",
+            '\n' => "\\n",
+            '\r' => "\\r",
+            '\t' => "\\t",
+            '\u{08}' => "\\b",
+            '\u{0C}' => "\\f",
+            ch if (ch as u32) < 0x20 => "",
+            '<' | '>' | '&' if options.escape_html => "",
+            ch if !ch.is_ascii() && options.ascii_only => "",
+            _ => continue,
+        };
+
+        out.write_str(&string[start..index])?;
+        start = index + ch.len_utf8();
+
+        if escape.is_empty() {
+            // above U+FFFF as a surrogate pair
+            let mut units = [0; 2];
+            for unit in ch.encode_utf16(&mut units) {
+                write!(out, "\\u{:04x}", unit)?;
+            }
+        } else {
+            out.write_str(escape)?;
+        }
+    }
+
+    out.write_str(&string[start..])?;
+    out.write_char('"')
+}
+
+// Writes a finite `num` as ECMAScript's `Number.prototype.toString` does,
+// which RFC 8785 takes for its numbers.
+fn write_ecmascript(out: &mut dyn Write, num: f64) -> fmt::Result {
+    // negative zero too
+    if num == 0.0 {
+        return out.write_char('0');
+    }
+
+    if num < 0.0 {
+        out.write_char('-')?;
+    }
+
+    // `LowerExp` gives the shortest digits that read back the same, as `d.ddde-x`
+    let exponential = format!("{:e}", num.abs());
+    let (mantissa, exp) = exponential.split_once('e').unwrap_or((&exponential, "0"));
+    let digits = mantissa.replace('.', "");
+
+    // the decimal point goes after `point` digits
+    let len = digits.len() as i64;
+    let point = exp.parse::<i64>().unwrap_or_default() + 1;
+
+    match point {
+        point if len <= point && point <= 21 => {
+            out.write_str(&digits)?;
+            (len..point).try_for_each(|_| out.write_char('0'))
+        }
+
+        1..=21 => {
+            let (whole, fraction) = digits.split_at(point as usize);
+            write!(out, "{whole}.{fraction}")
+        }
+
+        -5..=0 => {
+            out.write_str("0.")?;
+            (point..0).try_for_each(|_| out.write_char('0'))?;
+            out.write_str(&digits)
+        }
+
+        _ => {
+            let (first, rest) = digits.split_at(1);
+            out.write_str(first)?;
+
+            if !rest.is_empty() {
+                write!(out, ".{rest}")?;
+            }
+
+            let sign = if point > 0 { '+' } else { '-' };
+            write!(out, "e{sign}{}", (point - 1).abs())
+        }
+    }
+}
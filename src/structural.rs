@@ -0,0 +1,356 @@
+/*
+    First pass over strict JSON finding where tokens start, a block of bytes at a time.
+*/
+
+#[cfg(target_arch = "x86_64")]
+use std::arch::x86_64::*;
+
+const BLOCK: usize = 64;
+
+// most blocks scanned by one refill, the positions of a refill are all kept at once
+const MAX_BLOCKS: usize = 64;
+
+/// Positions where tokens of strict JSON start, found ahead of the `Lexer`
+/// so it can jump over whitespace and through strings instead of walking every character.
+///
+/// Bytes are classified 64 at a time with AVX2 when the CPU has it, SSE2 otherwise on x86_64,
+/// portable code elsewhere, then quotes escaped by an odd run of backslashes are dropped
+/// and the bytes between quotes masked out. What is left are the structural characters,
+/// both quotes of every string and the first byte of every other run of non whitespace.
+///
+/// The input is scanned lazily in windows growing up to 4 KiB,
+/// so memory use doesn't grow with the size of the input.
+/// Invalid input can give meaningless positions after the first error,
+/// which the `Lexer` reports before getting there.
+#[derive(Debug)]
+pub(crate) struct Structurals<'s> {
+    input: &'s [u8],
+    kernel: Kernel,
+
+    // where the next block to scan starts
+    scanned: usize,
+
+    // blocks scanned by the next refill, doubling up to `MAX_BLOCKS`
+    // to keep lexers made for a single token cheap
+    blocks: usize,
+
+    // positions found by the last refill, `next` is the first not given out yet
+    positions: Vec<usize>,
+    next: usize,
+
+    // carried over from one block to the next
+    in_string: bool,
+    escaped: bool,
+    in_scalar: bool,
+}
+
+impl<'s> Structurals<'s> {
+    pub(crate) fn new(input: &'s str) -> Self {
+        Self {
+            input: input.as_bytes(),
+            kernel: Kernel::detect(),
+            scanned: 0,
+            blocks: 1,
+            positions: Vec::new(),
+            next: 0,
+            in_string: false,
+            escaped: false,
+            in_scalar: false,
+        }
+    }
+
+    /// First position at or after `offset`, positions have to be asked for in order.
+    pub(crate) fn next_from(&mut self, offset: usize) -> Option<usize> {
+        loop {
+            while let Some(&position) = self.positions.get(self.next) {
+                if position >= offset {
+                    return Some(position);
+                }
+
+                self.next += 1;
+            }
+
+            if !self.refill() {
+                return None;
+            }
+        }
+    }
+
+    // Scans the next window of blocks, returns whether there was anything left to scan.
+    fn refill(&mut self) -> bool {
+        if self.scanned >= self.input.len() {
+            return false;
+        }
+
+        self.positions.clear();
+        self.next = 0;
+
+        let end = (self.scanned + self.blocks * BLOCK).min(self.input.len());
+        while self.scanned < end {
+            let masks = match self.input[self.scanned..].first_chunk::<BLOCK>() {
+                Some(block) => self.kernel.masks(block),
+
+                // the last block is padded with whitespace
+                None => {
+                    let rest = &self.input[self.scanned..];
+                    let mut block = [b' '; BLOCK];
+                    block[..rest.len()].copy_from_slice(rest);
+
+                    self.kernel.masks(&block)
+                }
+            };
+
+            self.scan_block(masks);
+            self.scanned += BLOCK;
+        }
+
+        self.blocks = (self.blocks * 2).min(MAX_BLOCKS);
+        true
+    }
+
+    // Turns the masks of the block at `self.scanned` into positions.
+    fn scan_block(&mut self, masks: Masks) {
+        let escaped = escaped_chars(masks.backslash, &mut self.escaped);
+        let quotes = masks.quote & !escaped;
+
+        // set from each opening quote up to, but not including, its closing quote
+        let in_string = prefix_xor(quotes) ^ carry(self.in_string);
+        self.in_string = in_string >> 63 == 1;
+
+        let scalar = !(masks.whitespace | masks.structural | masks.quote | in_string);
+        let scalar_starts = scalar & !(scalar << 1 | self.in_scalar as u64);
+        self.in_scalar = scalar >> 63 == 1;
+
+        let mut starts = (masks.structural & !in_string) | quotes | scalar_starts;
+        while starts != 0 {
+            self.positions
+                .push(self.scanned + starts.trailing_zeros() as usize);
+            starts &= starts - 1;
+        }
+    }
+}
+
+// Bit masks of the bytes of one block, bit `i` standing for byte `i`.
+#[derive(Debug, Default, PartialEq, Eq)]
+struct Masks {
+    quote: u64,
+    backslash: u64,
+    whitespace: u64,
+
+    // `{`, `}`, `[`, `]`, `:` and `,`
+    structural: u64,
+}
+
+// How blocks are classified, picked once per `Structurals`.
+#[derive(Debug, Clone, Copy)]
+enum Kernel {
+    #[cfg(not(target_arch = "x86_64"))]
+    Scalar,
+
+    #[cfg(target_arch = "x86_64")]
+    Sse2,
+
+    #[cfg(target_arch = "x86_64")]
+    Avx2,
+}
+
+impl Kernel {
+    fn detect() -> Self {
+        // SSE2 is part of x86_64 itself
+        #[cfg(target_arch = "x86_64")]
+        return match is_x86_feature_detected!("avx2") {
+            true => Self::Avx2,
+            false => Self::Sse2,
+        };
+
+        #[cfg(not(target_arch = "x86_64"))]
+        Self::Scalar
+    }
+
+    fn masks(self, block: &[u8; BLOCK]) -> Masks {
+        match self {
+            #[cfg(not(target_arch = "x86_64"))]
+            Self::Scalar => scalar_masks(block),
+
+            // SAFETY: every x86_64 CPU has SSE2
+            #[cfg(target_arch = "x86_64")]
+            Self::Sse2 => unsafe { sse2_masks(block) },
+
+            // SAFETY: only picked once AVX2 was detected
+            #[cfg(target_arch = "x86_64")]
+            Self::Avx2 => unsafe { avx2_masks(block) },
+        }
+    }
+}
+
+// --- HELPER FUNCTIONS --- //
+
+#[cfg(any(test, not(target_arch = "x86_64")))]
+fn scalar_masks(block: &[u8; BLOCK]) -> Masks {
+    let mut masks = Masks::default();
+
+    for (index, &byte) in block.iter().enumerate() {
+        let bit = 1 << index;
+
+        match byte {
+            b'"' => masks.quote |= bit,
+            b'\\' => masks.backslash |= bit,
+            b' ' | b'\t' | b'\n' | b'\r' => masks.whitespace |= bit,
+            b'{' | b'}' | b'[' | b']' | b':' | b',' => masks.structural |= bit,
+            _ => {}
+        }
+    }
+
+    masks
+}
+
+#[cfg(target_arch = "x86_64")]
+#[target_feature(enable = "sse2")]
+fn sse2_masks(block: &[u8; BLOCK]) -> Masks {
+    let mut masks = Masks::default();
+
+    for (index, chunk) in block.chunks_exact(16).enumerate() {
+        // SAFETY: `chunk` is 16 bytes long, unaligned loads are fine
+        let bytes = unsafe { _mm_loadu_si128(chunk.as_ptr().cast()) };
+        let eq = |byte: u8| {
+            let matches = _mm_cmpeq_epi8(bytes, _mm_set1_epi8(byte as i8));
+            (_mm_movemask_epi8(matches) as u16 as u64) << (index * 16)
+        };
+
+        masks.quote |= eq(b'"');
+        masks.backslash |= eq(b'\\');
+        masks.whitespace |= eq(b' ') | eq(b'\t') | eq(b'\n') | eq(b'\r');
+        masks.structural |= eq(b'{') | eq(b'}') | eq(b'[') | eq(b']') | eq(b':') | eq(b',');
+    }
+
+    masks
+}
+
+#[cfg(target_arch = "x86_64")]
+#[target_feature(enable = "avx2")]
+fn avx2_masks(block: &[u8; BLOCK]) -> Masks {
+    let mut masks = Masks::default();
+
+    for (index, chunk) in block.chunks_exact(32).enumerate() {
+        // SAFETY: `chunk` is 32 bytes long, unaligned loads are fine
+        let bytes = unsafe { _mm256_loadu_si256(chunk.as_ptr().cast()) };
+        let eq = |byte: u8| {
+            let matches = _mm256_cmpeq_epi8(bytes, _mm256_set1_epi8(byte as i8));
+            (_mm256_movemask_epi8(matches) as u32 as u64) << (index * 32)
+        };
+
+        masks.quote |= eq(b'"');
+        masks.backslash |= eq(b'\\');
+        masks.whitespace |= eq(b' ') | eq(b'\t') | eq(b'\n') | eq(b'\r');
+        masks.structural |= eq(b'{') | eq(b'}') | eq(b'[') | eq(b']') | eq(b':') | eq(b',');
+    }
+
+    masks
+}
+
+// Characters escaped by the backslashes of `backslash`, a backslash escaped itself escapes nothing.
+// `carry` tells whether the first byte is escaped by the previous block and is updated for the next one.
+fn escaped_chars(backslash: u64, carry: &mut bool) -> u64 {
+    let mut escaped = *carry as u64;
+    let mut rest = backslash;
+    *carry = false;
+
+    // backslashes are rare enough for a loop over them
+    while rest != 0 {
+        let index = rest.trailing_zeros();
+        rest &= rest - 1;
+
+        if escaped >> index & 1 == 1 {
+            continue;
+        }
+
+        match index {
+            63 => *carry = true,
+            _ => escaped |= 1 << (index + 1),
+        }
+    }
+
+    escaped
+}
+
+// Bit `i` is the xor of bits `0..=i` of `bits`.
+fn prefix_xor(mut bits: u64) -> u64 {
+    for shift in [1, 2, 4, 8, 16, 32] {
+        bits ^= bits << shift;
+    }
+
+    bits
+}
+
+// A mask of all ones if `set`.
+fn carry(set: bool) -> u64 {
+    match set {
+        true => u64::MAX,
+        false => 0,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // Bytes the kernels look for, along with neighbours and ones with the high bit set.
+    const BYTES: &[u8] = b"\"\\ \t\n\r{}[]:,\x0b\x0c\x00!/;\\\x80\xa0\xff";
+
+    // Every kernel has to classify `block` like the portable one.
+    fn assert_kernels_agree(block: &[u8; BLOCK]) {
+        let expected = scalar_masks(block);
+
+        #[cfg(target_arch = "x86_64")]
+        {
+            // SAFETY: every x86_64 CPU has SSE2
+            assert_eq!(unsafe { sse2_masks(block) }, expected, "{block:?}");
+
+            if is_x86_feature_detected!("avx2") {
+                // SAFETY: AVX2 was just detected
+                assert_eq!(unsafe { avx2_masks(block) }, expected, "{block:?}");
+            }
+        }
+
+        #[cfg(not(target_arch = "x86_64"))]
+        let _ = expected;
+    }
+
+    #[test]
+    fn kernels_agree() {
+        for byte in 0..=u8::MAX {
+            assert_kernels_agree(&[byte; BLOCK]);
+        }
+
+        // each byte alone at every lane, the ends of the 16 and 32 byte lanes included
+        for &byte in BYTES {
+            for index in 0..BLOCK {
+                let mut block = [b'a'; BLOCK];
+                block[index] = byte;
+                assert_kernels_agree(&block);
+            }
+        }
+
+        // xorshift, mixing the bytes above with anything else
+        let mut state = 0x9E37_79B9_7F4A_7C15_u64;
+        let mut next = || {
+            state ^= state << 13;
+            state ^= state >> 7;
+            state ^= state << 17;
+            state
+        };
+
+        for _ in 0..10_000 {
+            let mut block = [0; BLOCK];
+            for byte in &mut block {
+                let random = next();
+                *byte = match random % 2 {
+                    0 => BYTES[(random >> 8) as usize % BYTES.len()],
+                    _ => (random >> 8) as u8,
+                };
+            }
+
+            assert_kernels_agree(&block);
+        }
+    }
+}
@@ -1,4 +1,371 @@
-// pub trait JsonDeserialize {
-//     fn new_instance() -> Self
+use std::collections::{BTreeMap, HashMap};
+use std::fmt;
+use std::hash::BuildHasher;
 
-// }
+use crate::error::{Error, ErrorKind};
+use crate::lexer::{Span, Token};
+use crate::parser::{Parser, wrong_token};
+use crate::ser::Serializer;
+use crate::value::{JsonValue, Number};
+
+type Result<T> = std::result::Result<T, Error>;
+
+/// Types that can be read straight out of the `Parser`'s tokens.
+///
+/// Usually derived with `#[derive(JsonDeserialize)]`
+/// which reads a JSON object into a struct field by field.
+pub trait JsonDeserialize: Sized {
+    /// Reads exactly one JSON value from `parser`.
+    fn deserialize(parser: &mut Parser<'_>) -> Result<Self>;
+
+    /// Called by derived implementations when the key of a field of type `Self` is absent,
+    /// `span` is the span of the object.
+    fn missing(parser: &Parser<'_>, field: &'static str, span: Span) -> Result<Self> {
+        Err(parser.error(ErrorKind::MissingField(field), span))
+    }
+}
+
+/// Reads `input` as a single `T`.
+pub fn from_str<T: JsonDeserialize>(input: &str) -> Result<T> {
+    Parser::new(input).deserialize()
+}
+
+macro_rules! int_impl {
+    ($($ty:ty),* $(,)?) => {
+        $(
+            impl JsonDeserialize for $ty {
+                fn deserialize(parser: &mut Parser<'_>) -> Result<Self> {
+                    match parser.eof_next("a number")? {
+                        Token::Int(num, span) => <$ty>::try_from(num).map_err(|_| {
+                            parser.error(ErrorKind::OutOfRange(stringify!($ty)), span)
+                        }),
+
+                        // integers beyond `i64` are lexed as floats, their text is still exact
+                        Token::Float(_, span) if integer_literal(parser.text(span)).is_some() => {
+                            integer_literal(parser.text(span))
+                                .and_then(|num| <$ty>::try_from(num).ok())
+                                .ok_or_else(|| {
+                                    parser.error(ErrorKind::OutOfRange(stringify!($ty)), span)
+                                })
+                        }
+
+                        token => wrong_token!(parser, token, concat!("`", stringify!($ty), "`")),
+                    }
+                }
+            }
+        )*
+    };
+}
+
+int_impl!(i8, i16, i32, i64, isize, u8, u16, u32, u64, usize);
+
+macro_rules! float_impl {
+    ($($ty:ty),* $(,)?) => {
+        $(
+            impl JsonDeserialize for $ty {
+                fn deserialize(parser: &mut Parser<'_>) -> Result<Self> {
+                    match parser.eof_next("a number")? {
+                        Token::Int(num, _) => Ok(num as $ty),
+                        Token::Float(num, _) => Ok(num as $ty),
+
+                        token => wrong_token!(parser, token, concat!("`", stringify!($ty), "`")),
+                    }
+                }
+            }
+        )*
+    };
+}
+
+float_impl!(f32, f64);
+
+impl JsonDeserialize for bool {
+    fn deserialize(parser: &mut Parser<'_>) -> Result<Self> {
+        match parser.eof_next("a bool")? {
+            Token::Bool(val, _) => Ok(val),
+            token => wrong_token!(parser, token, "`bool`"),
+        }
+    }
+}
+
+impl JsonDeserialize for String {
+    fn deserialize(parser: &mut Parser<'_>) -> Result<Self> {
+        match parser.eof_next("a string")? {
+            Token::String(string, _) => Ok(string.into_owned()),
+            token => wrong_token!(parser, token, "`String`"),
+        }
+    }
+}
+
+impl JsonDeserialize for () {
+    fn deserialize(parser: &mut Parser<'_>) -> Result<Self> {
+        match parser.eof_next("`null`")? {
+            Token::Null(_) => Ok(()),
+            token => wrong_token!(parser, token, "`null`"),
+        }
+    }
+}
+
+impl JsonDeserialize for JsonValue {
+    fn deserialize(parser: &mut Parser<'_>) -> Result<Self> {
+        parser.value()
+    }
+}
+
+impl<T: JsonDeserialize> JsonDeserialize for Option<T> {
+    fn deserialize(parser: &mut Parser<'_>) -> Result<Self> {
+        if let Some(Token::Null(_)) = parser.peek()? {
+            parser.next()?;
+            return Ok(None);
+        }
+
+        T::deserialize(parser).map(Some)
+    }
+
+    /// Absent keys are read as `None`.
+    fn missing(_: &Parser<'_>, _: &'static str, _: Span) -> Result<Self> {
+        Ok(None)
+    }
+}
+
+impl<T: JsonDeserialize> JsonDeserialize for Box<T> {
+    fn deserialize(parser: &mut Parser<'_>) -> Result<Self> {
+        T::deserialize(parser).map(Box::new)
+    }
+}
+
+impl<T: JsonDeserialize> JsonDeserialize for Vec<T> {
+    fn deserialize(parser: &mut Parser<'_>) -> Result<Self> {
+        parser.begin_array()?;
+
+        let mut vec = Vec::new();
+        let mut first = true;
+
+        while parser.next_element(first)? {
+            first = false;
+
+            vec.push(T::deserialize(parser)?);
+        }
+
+        Ok(vec)
+    }
+}
+
+impl<T: JsonDeserialize> JsonDeserialize for BTreeMap<String, T> {
+    fn deserialize(parser: &mut Parser<'_>) -> Result<Self> {
+        parser.begin_object()?;
+
+        let mut map = BTreeMap::new();
+        let mut first = true;
+
+        while let Some((key, _)) = parser.next_key(first)? {
+            first = false;
+
+            map.insert(key, T::deserialize(parser)?);
+        }
+
+        Ok(map)
+    }
+}
+
+impl<T: JsonDeserialize, S: BuildHasher + Default> JsonDeserialize for HashMap<String, T, S> {
+    fn deserialize(parser: &mut Parser<'_>) -> Result<Self> {
+        parser.begin_object()?;
+
+        let mut map = HashMap::default();
+        let mut first = true;
+
+        while let Some((key, _)) = parser.next_key(first)? {
+            first = false;
+
+            map.insert(key, T::deserialize(parser)?);
+        }
+
+        Ok(map)
+    }
+}
+
+/// Types that can be written out as JSON through a `Serializer`.
+///
+/// Usually derived with `#[derive(JsonSerialize)]`
+/// which writes a struct as an object with one member per field.
+pub trait JsonSerialize {
+    /// Writes exactly one JSON value into `ser`.
+    fn serialize(&self, ser: &mut Serializer<'_>) -> fmt::Result;
+}
+
+macro_rules! ser_int_impl {
+    ($method:ident: $($ty:ty),* $(,)?) => {
+        $(
+            impl JsonSerialize for $ty {
+                fn serialize(&self, ser: &mut Serializer<'_>) -> fmt::Result {
+                    ser.$method((*self).into())
+                }
+            }
+        )*
+    };
+}
+
+ser_int_impl!(write_i64: i8, i16, i32, i64);
+ser_int_impl!(write_u64: u8, u16, u32, u64);
+ser_int_impl!(write_f64: f32, f64);
+
+impl JsonSerialize for isize {
+    fn serialize(&self, ser: &mut Serializer<'_>) -> fmt::Result {
+        ser.write_i64(*self as i64)
+    }
+}
+
+impl JsonSerialize for usize {
+    fn serialize(&self, ser: &mut Serializer<'_>) -> fmt::Result {
+        ser.write_u64(*self as u64)
+    }
+}
+
+impl JsonSerialize for bool {
+    fn serialize(&self, ser: &mut Serializer<'_>) -> fmt::Result {
+        ser.write_bool(*self)
+    }
+}
+
+impl JsonSerialize for str {
+    fn serialize(&self, ser: &mut Serializer<'_>) -> fmt::Result {
+        ser.write_str(self)
+    }
+}
+
+impl JsonSerialize for String {
+    fn serialize(&self, ser: &mut Serializer<'_>) -> fmt::Result {
+        ser.write_str(self)
+    }
+}
+
+impl JsonSerialize for () {
+    fn serialize(&self, ser: &mut Serializer<'_>) -> fmt::Result {
+        ser.write_null()
+    }
+}
+
+impl<T: JsonSerialize + ?Sized> JsonSerialize for &T {
+    fn serialize(&self, ser: &mut Serializer<'_>) -> fmt::Result {
+        (**self).serialize(ser)
+    }
+}
+
+impl<T: JsonSerialize + ?Sized> JsonSerialize for Box<T> {
+    fn serialize(&self, ser: &mut Serializer<'_>) -> fmt::Result {
+        (**self).serialize(ser)
+    }
+}
+
+impl<T: JsonSerialize> JsonSerialize for Option<T> {
+    fn serialize(&self, ser: &mut Serializer<'_>) -> fmt::Result {
+        match self {
+            Some(val) => val.serialize(ser),
+            None => ser.write_null(),
+        }
+    }
+}
+
+impl<T: JsonSerialize> JsonSerialize for [T] {
+    fn serialize(&self, ser: &mut Serializer<'_>) -> fmt::Result {
+        ser.begin_array()?;
+
+        for item in self {
+            ser.element()?;
+            item.serialize(ser)?;
+        }
+
+        ser.end_array()
+    }
+}
+
+impl<T: JsonSerialize, const N: usize> JsonSerialize for [T; N] {
+    fn serialize(&self, ser: &mut Serializer<'_>) -> fmt::Result {
+        self.as_slice().serialize(ser)
+    }
+}
+
+impl<T: JsonSerialize> JsonSerialize for Vec<T> {
+    fn serialize(&self, ser: &mut Serializer<'_>) -> fmt::Result {
+        self.as_slice().serialize(ser)
+    }
+}
+
+// Objects out of any iterator of key/value pairs.
+fn serialize_map<'a, K, V, I>(iter: I, ser: &mut Serializer<'_>) -> fmt::Result
+where
+    K: AsRef<str> + 'a,
+    V: JsonSerialize + 'a,
+    I: Iterator<Item = (&'a K, &'a V)>,
+{
+    ser.begin_object()?;
+
+    for (key, value) in iter {
+        ser.key(key.as_ref())?;
+        value.serialize(ser)?;
+    }
+
+    ser.end_object()
+}
+
+impl<K: AsRef<str>, V: JsonSerialize> JsonSerialize for BTreeMap<K, V> {
+    fn serialize(&self, ser: &mut Serializer<'_>) -> fmt::Result {
+        serialize_map(self.iter(), ser)
+    }
+}
+
+impl<K: AsRef<str>, V: JsonSerialize, S> JsonSerialize for HashMap<K, V, S> {
+    fn serialize(&self, ser: &mut Serializer<'_>) -> fmt::Result {
+        serialize_map(self.iter(), ser)
+    }
+}
+
+impl JsonSerialize for Number {
+    fn serialize(&self, ser: &mut Serializer<'_>) -> fmt::Result {
+        match *self {
+            Number::Int(num) => ser.write_i64(num),
+            Number::Float(num) => ser.write_f64(num),
+        }
+    }
+}
+
+impl JsonSerialize for JsonValue {
+    fn serialize(&self, ser: &mut Serializer<'_>) -> fmt::Result {
+        match self {
+            JsonValue::Object(map) => serialize_map(map.iter(), ser),
+            JsonValue::Array(vec) => vec.serialize(ser),
+            JsonValue::String(string) => ser.write_str(string),
+            JsonValue::Number(num) => num.serialize(ser),
+            JsonValue::Bool(val) => ser.write_bool(*val),
+            JsonValue::Null => ser.write_null(),
+        }
+    }
+}
+
+// --- HELPER FUNCTIONS --- //
+
+// The value of `text` if it is an integer literal, decimal or JSON5 hex,
+// saturated to the bounds of an `i128` which no Rust integer reaches.
+fn integer_literal(text: &str) -> Option<i128> {
+    let (negative, unsigned) = match text.as_bytes().first()? {
+        b'-' => (true, &text[1..]),
+        b'+' => (false, &text[1..]),
+        _ => (false, text),
+    };
+
+    let (digits, radix) = match unsigned.strip_prefix("0x").or(unsigned.strip_prefix("0X")) {
+        Some(digits) => (digits, 16),
+        None => (unsigned, 10),
+    };
+
+    if digits.is_empty() || !digits.chars().all(|ch| ch.is_digit(radix)) {
+        return None;
+    }
+
+    let num = i128::from_str_radix(digits, radix).unwrap_or(i128::MAX);
+
+    match negative {
+        true => Some(-num),
+        false => Some(num),
+    }
+}
@@ -0,0 +1,260 @@
+/*
+    Document model produced by the parser.
+*/
+
+use std::ops::{Index, IndexMut};
+
+use crate::map::{Entry, Map};
+
+/// Returned by the indexing operators when a key or index is missing.
+static NULL: JsonValue = JsonValue::Null;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Number {
+    Int(i64),
+    Float(f64),
+}
+
+impl Number {
+    /// Returns the number as an `i64` if it was written as an integer.
+    pub fn as_i64(&self) -> Option<i64> {
+        match *self {
+            Self::Int(num) => Some(num),
+            Self::Float(_) => None,
+        }
+    }
+
+    /// Returns the number as an `u64` if it was written as a non negative integer.
+    pub fn as_u64(&self) -> Option<u64> {
+        match *self {
+            Self::Int(num) => u64::try_from(num).ok(),
+            Self::Float(_) => None,
+        }
+    }
+
+    /// Returns the number as an `f64`, integers are converted.
+    pub fn as_f64(&self) -> f64 {
+        match *self {
+            Self::Int(num) => num as f64,
+            Self::Float(num) => num,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum JsonValue {
+    Object(Map),
+    Array(Vec<JsonValue>),
+    String(String),
+    Number(Number),
+    Bool(bool),
+    Null,
+}
+
+impl JsonValue {
+    pub fn is_null(&self) -> bool {
+        matches!(self, Self::Null)
+    }
+
+    pub fn as_bool(&self) -> Option<bool> {
+        match *self {
+            Self::Bool(val) => Some(val),
+            _ => None,
+        }
+    }
+
+    pub fn as_str(&self) -> Option<&str> {
+        match self {
+            Self::String(string) => Some(string),
+            _ => None,
+        }
+    }
+
+    pub fn as_number(&self) -> Option<Number> {
+        match *self {
+            Self::Number(num) => Some(num),
+            _ => None,
+        }
+    }
+
+    pub fn as_i64(&self) -> Option<i64> {
+        self.as_number().and_then(|num| num.as_i64())
+    }
+
+    pub fn as_u64(&self) -> Option<u64> {
+        self.as_number().and_then(|num| num.as_u64())
+    }
+
+    pub fn as_f64(&self) -> Option<f64> {
+        self.as_number().map(|num| num.as_f64())
+    }
+
+    pub fn as_array(&self) -> Option<&Vec<JsonValue>> {
+        match self {
+            Self::Array(vec) => Some(vec),
+            _ => None,
+        }
+    }
+
+    pub fn as_array_mut(&mut self) -> Option<&mut Vec<JsonValue>> {
+        match self {
+            Self::Array(vec) => Some(vec),
+            _ => None,
+        }
+    }
+
+    pub fn as_object(&self) -> Option<&Map> {
+        match self {
+            Self::Object(map) => Some(map),
+            _ => None,
+        }
+    }
+
+    pub fn as_object_mut(&mut self) -> Option<&mut Map> {
+        match self {
+            Self::Object(map) => Some(map),
+            _ => None,
+        }
+    }
+
+    /// Looks up `key` if this is an object.
+    pub fn get(&self, key: &str) -> Option<&JsonValue> {
+        self.as_object().and_then(|map| map.get(key))
+    }
+
+    pub fn get_mut(&mut self, key: &str) -> Option<&mut JsonValue> {
+        self.as_object_mut().and_then(|map| map.get_mut(key))
+    }
+
+    /// Entry of `key` for building documents,
+    /// `Null` is turned into an empty object first.
+    /// Returns `None` if the value is neither an object nor `Null`.
+    pub fn entry(&mut self, key: impl Into<String>) -> Option<Entry<'_>> {
+        if self.is_null() {
+            *self = Self::Object(Map::new());
+        }
+
+        self.as_object_mut().map(|map| map.entry(key))
+    }
+
+    /// Replaces the value with `Null`, returning the old one.
+    pub fn take(&mut self) -> JsonValue {
+        std::mem::replace(self, Self::Null)
+    }
+}
+
+impl std::str::FromStr for JsonValue {
+    type Err = crate::error::Error;
+
+    fn from_str(input: &str) -> Result<Self, Self::Err> {
+        crate::parser::Parser::new(input).parse()
+    }
+}
+
+impl Index<&str> for JsonValue {
+    type Output = JsonValue;
+
+    /// Missing keys and non objects give `Null`.
+    fn index(&self, key: &str) -> &JsonValue {
+        self.get(key).unwrap_or(&NULL)
+    }
+}
+
+impl Index<usize> for JsonValue {
+    type Output = JsonValue;
+
+    /// Out of bounds indices and non arrays give `Null`.
+    fn index(&self, index: usize) -> &JsonValue {
+        self.as_array()
+            .and_then(|vec| vec.get(index))
+            .unwrap_or(&NULL)
+    }
+}
+
+impl IndexMut<&str> for JsonValue {
+    /// Inserts `Null` under missing keys, `Null` itself becomes an object.
+    ///
+    /// # Panics
+    /// If the value is neither an object nor `Null`.
+    fn index_mut(&mut self, key: &str) -> &mut JsonValue {
+        match self.entry(key) {
+            Some(entry) => entry.or_null(),
+            None => panic!("cannot index into a non object JSON value with {key:?}"),
+        }
+    }
+}
+
+impl IndexMut<usize> for JsonValue {
+    /// # Panics
+    /// If the value is not an array or the index is out of bounds.
+    fn index_mut(&mut self, index: usize) -> &mut JsonValue {
+        match self.as_array_mut() {
+            Some(vec) => {
+                let len = vec.len();
+                match vec.get_mut(index) {
+                    Some(val) => val,
+                    None => panic!("index {index} out of bounds of an array of length {len}"),
+                }
+            }
+
+            None => panic!("cannot index into a non array JSON value with {index}"),
+        }
+    }
+}
+
+macro_rules! from_impl {
+    ($($ty:ty => $variant:ident),* $(,)?) => {
+        $(
+            impl From<$ty> for JsonValue {
+                fn from(val: $ty) -> Self {
+                    Self::$variant(val.into())
+                }
+            }
+        )*
+    };
+}
+
+from_impl! {
+    bool => Bool,
+    String => String,
+    &str => String,
+    Vec<JsonValue> => Array,
+    Map => Object,
+    Number => Number,
+}
+
+macro_rules! from_num_impl {
+    ($($ty:ty => $variant:ident),* $(,)?) => {
+        $(
+            impl From<$ty> for JsonValue {
+                fn from(val: $ty) -> Self {
+                    Self::Number(Number::$variant(val.into()))
+                }
+            }
+        )*
+    };
+}
+
+from_num_impl! {
+    i8 => Int,
+    i16 => Int,
+    i32 => Int,
+    i64 => Int,
+    u8 => Int,
+    u16 => Int,
+    u32 => Int,
+    f32 => Float,
+    f64 => Float,
+}
+
+impl From<()> for JsonValue {
+    fn from(_: ()) -> Self {
+        Self::Null
+    }
+}
+
+impl<T: Into<JsonValue>> From<Option<T>> for JsonValue {
+    fn from(val: Option<T>) -> Self {
+        val.map_or(Self::Null, Into::into)
+    }
+}
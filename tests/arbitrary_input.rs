@@ -0,0 +1,158 @@
+use iovera::{Dialect, Error, Events, Lexer, Parser, Progress, PushParser, ReadLexer};
+
+mod common;
+use common::Rng;
+
+const DIALECTS: [Dialect; 3] = [Dialect::Strict, Dialect::Jsonc, Dialect::Json5];
+
+// Documents using every kind of token, each dialect's extensions included.
+const SEEDS: &[&str] = &[
+    "{\"a\": [1, -2.5e+3, true, false, null], \"b\": {\"c\": \"d\"}}",
+    "[\"esc \\\" \\\\ \\/ \\b \\f \\n \\r \\t \\u00e9 \\ud83d\\ude00\"]",
+    "{\"ключ\": \"значение ✓ 😀\", \"e\": 0.5E-7}",
+    "[1, /* two */ 2, // three\n 3,]",
+    "{unquoted: 'single', hex: -0x1F, inf: +Infinity, nan: NaN, dots: [.5, 5.],}",
+    "['multi\\\nline \\x41 \\v \\0', \"\\u2028\"]",
+    "-0.0e-0",
+    "\"\\ud800\\udc00\"",
+];
+
+// Every lexer and parser over `input` in every dialect has to give `Ok` or `Err`,
+// with errors pointing into the input.
+fn assert_no_panic(input: &str) {
+    for dialect in DIALECTS {
+        for result in Lexer::with_dialect(input, dialect) {
+            if let Err(err) = result {
+                check_error(&err, input);
+            }
+        }
+
+        for result in Events::with_dialect(input, dialect) {
+            if let Err(err) = result {
+                check_error(&err, input);
+            }
+        }
+
+        if let Err(err) = Parser::with_dialect(input, dialect).parse() {
+            check_error(&err, input);
+        }
+
+        let recovered = Parser::with_dialect(input, dialect).parse_recovering();
+        for err in recovered.errors {
+            check_error(&err, input);
+        }
+
+        for span in recovered.placeholders {
+            let (start, end) = span.as_tuple();
+            assert!(start <= end && end <= input.len());
+        }
+    }
+}
+
+// Same for the readers, which get the raw bytes and so can be cut inside a character.
+fn assert_no_panic_bytes(bytes: &[u8]) {
+    for dialect in DIALECTS {
+        let mut lexer = ReadLexer::with_dialect(bytes, dialect);
+        lexer.set_chunk_size(3);
+        lexer.for_each(drop);
+
+        let mut parser = PushParser::with_dialect(dialect);
+        let mut result = Ok(());
+
+        for piece in bytes.chunks(5) {
+            result = result.and_then(|()| parser.feed(piece));
+        }
+
+        let _ = result.and_then(|()| parser.finish()).and_then(|()| {
+            loop {
+                if let Progress::Done = parser.next_event()? {
+                    return Ok(());
+                }
+            }
+        });
+    }
+}
+
+fn check_error(err: &Error, input: &str) {
+    let (start, end) = err.span().as_tuple();
+    assert!(
+        start <= end && end <= input.len(),
+        "{err} spans {start}..{end} of {input:?}"
+    );
+
+    let _ = err.to_string();
+    let _ = err.snippet(input).to_string();
+}
+
+#[test]
+fn truncated_at_every_offset() {
+    for seed in SEEDS {
+        for offset in 0..=seed.len() {
+            if let Some(prefix) = seed.get(..offset) {
+                assert_no_panic(prefix);
+            }
+
+            assert_no_panic_bytes(&seed.as_bytes()[..offset]);
+        }
+    }
+}
+
+#[test]
+fn every_character_in_every_place() {
+    let chars = (0..0x80u8)
+        .map(char::from)
+        .chain(['\u{A0}', '\u{FEFF}', '\u{2028}', 'é', 'ж', '✓', '😀']);
+
+    for ch in chars {
+        for template in [
+            "#",
+            "[#]",
+            "{\"a\"#}",
+            "{#: 1}",
+            "\"#\"",
+            "\"\\#\"",
+            "'#'",
+            "1#",
+            "-#",
+            "0x#",
+            "t#",
+            "/#",
+            "/*#",
+            "\"\\u#\"",
+            "\"\\x#\"",
+            "\"\\ud800\\#\"",
+        ] {
+            assert_no_panic(&template.replace('#', ch.encode_utf8(&mut [0; 4])));
+        }
+    }
+}
+
+#[test]
+fn random_edits() {
+    let mut rng = Rng(0x2545_F491_4F6C_DD1D);
+    let alphabet: Vec<char> = "{}[]:,\"'\\/*-+.0123456789eExXuabtfnrl \t\n\r\u{c}@#é😀"
+        .chars()
+        .collect();
+
+    for _ in 0..3000 {
+        let mut chars: Vec<char> = rng.pick(SEEDS).chars().collect();
+
+        for _ in 0..1 + rng.below(4) {
+            let at = rng.below(chars.len() + 1);
+            let ch = *rng.pick(&alphabet);
+
+            match rng.below(3) {
+                0 => chars.insert(at, ch),
+                _ if at == chars.len() => {}
+                1 => chars[at] = ch,
+                _ => drop(chars.remove(at)),
+            }
+        }
+
+        let input: String = chars.into_iter().collect();
+        assert_no_panic(&input);
+
+        let bytes = input.as_bytes();
+        assert_no_panic_bytes(&bytes[..rng.below(bytes.len() + 1)]);
+    }
+}
@@ -0,0 +1,179 @@
+use iovera::{
+    JsonSerialize, JsonValue, SerializeError, SerializeOptions, to_canonical_vec, to_string_with,
+    to_writer_with,
+};
+
+fn canonical(text: &str) -> String {
+    let value: JsonValue = text.parse().unwrap();
+    String::from_utf8(to_canonical_vec(&value).unwrap()).unwrap()
+}
+
+#[test]
+fn rfc_8785_examples() {
+    // section 3.2.2
+    let text = r#"{
+        "numbers": [333333333.33333329, 1E30, 4.50, 2e-3, 0.000000000000000000000000001],
+        "string": "\u20ac$\u000F\u000aA'\u0042\u0022\u005c\\\"\/",
+        "literals": [null, true, false]
+    }"#;
+    assert_eq!(
+        canonical(text),
+        r#"{"literals":[null,true,false],"numbers":[333333333.3333333,1e+30,4.5,0.002,1e-27],"string":"€$\u000f\nA'B\"\\\\\"/"}"#
+    );
+
+    // section 3.2.3, by UTF-16 code units the emoji comes before U+FB33
+    let text = r#"{
+        "€": "Euro Sign",
+        "\r": "Carriage Return",
+        "דּ": "Hebrew Letter Dalet With Dagesh",
+        "1": "One",
+        "😀": "Emoji: Grinning Face",
+        "\u0080": "Control",
+        "ö": "Latin Small Letter O With Diaeresis"
+    }"#;
+    let keys: Vec<JsonValue> = canonical(text)
+        .parse::<JsonValue>()
+        .unwrap()
+        .as_object()
+        .unwrap()
+        .iter()
+        .map(|(_, value)| value.clone())
+        .collect();
+    assert_eq!(
+        keys.iter()
+            .filter_map(JsonValue::as_str)
+            .collect::<Vec<_>>(),
+        [
+            "Carriage Return",
+            "One",
+            "Control",
+            "Latin Small Letter O With Diaeresis",
+            "Euro Sign",
+            "Emoji: Grinning Face",
+            "Hebrew Letter Dalet With Dagesh",
+        ]
+    );
+}
+
+#[test]
+fn numbers_as_ecmascript_writes_them() {
+    // appendix B
+    const BITS: &[(u64, &str)] = &[
+        (0x0000000000000000, "0"),
+        (0x8000000000000000, "0"),
+        (0x0000000000000001, "5e-324"),
+        (0x8000000000000001, "-5e-324"),
+        (0x7fefffffffffffff, "1.7976931348623157e+308"),
+        (0xffefffffffffffff, "-1.7976931348623157e+308"),
+        (0x4340000000000000, "9007199254740992"),
+        (0xc340000000000000, "-9007199254740992"),
+        (0x4430000000000000, "295147905179352830000"),
+        (0x44b52d02c7e14af5, "9.999999999999997e+22"),
+        (0x44b52d02c7e14af6, "1e+23"),
+        (0x44b52d02c7e14af7, "1.0000000000000001e+23"),
+        (0x444b1ae4d6e2ef4e, "999999999999999700000"),
+        (0x444b1ae4d6e2ef4f, "999999999999999900000"),
+        (0x444b1ae4d6e2ef50, "1e+21"),
+        (0x3eb0c6f7a0b5ed8d, "0.000001"),
+        (0x3eb0c6f7a0b5ed8c, "9.999999999999997e-7"),
+        (0x41b3de4355555553, "333333333.3333332"),
+        (0x41b3de4355555554, "333333333.33333325"),
+        (0x41b3de4355555555, "333333333.3333333"),
+    ];
+
+    for &(bits, expected) in BITS {
+        let written = to_canonical_vec(&f64::from_bits(bits)).unwrap();
+        assert_eq!(String::from_utf8(written).unwrap(), expected, "{bits:#x}");
+    }
+
+    // integers are doubles too, written like the double they are exactly
+    assert_eq!(to_canonical_vec(&-42i64).unwrap(), b"-42");
+    assert_eq!(
+        to_canonical_vec(&(1u64 << 60)).unwrap(),
+        b"1152921504606847000"
+    );
+    assert_eq!(
+        to_canonical_vec(&i64::MIN).unwrap(),
+        b"-9223372036854776000"
+    );
+
+    // those no double holds exactly fail rather than being rounded
+    assert_eq!(
+        to_canonical_vec(&u64::MAX),
+        Err(SerializeError::InexactInteger(u64::MAX.into()))
+    );
+    assert_eq!(
+        to_canonical_vec(&((1i64 << 53) + 1)),
+        Err(SerializeError::InexactInteger((1 << 53) + 1))
+    );
+
+    // no canonical form at all
+    assert!(matches!(
+        to_canonical_vec(&f64::NAN),
+        Err(SerializeError::NonFiniteFloat(num)) if num.is_nan()
+    ));
+    assert_eq!(
+        to_canonical_vec(&vec![f64::INFINITY]),
+        Err(SerializeError::NonFiniteFloat(f64::INFINITY))
+    );
+}
+
+#[test]
+fn every_helper_reports_what_has_no_canonical_form() {
+    let options = SerializeOptions::canonical();
+    assert_eq!(
+        to_string_with(&[1.0, f64::NEG_INFINITY], &options),
+        Err(SerializeError::NonFiniteFloat(f64::NEG_INFINITY))
+    );
+
+    let mut written = Vec::new();
+    let err = to_writer_with(&mut written, &f64::NAN, &options).unwrap_err();
+    assert_eq!(err.kind(), std::io::ErrorKind::InvalidData);
+    assert!(matches!(
+        err.into_inner().unwrap().downcast_ref(),
+        Some(SerializeError::NonFiniteFloat(_))
+    ));
+
+    // everywhere else they are `null`
+    assert_eq!(
+        to_string_with(&f64::NAN, &SerializeOptions::pretty()).unwrap(),
+        "null"
+    );
+}
+
+#[test]
+fn the_same_bytes_whatever_the_input() {
+    #[derive(JsonSerialize)]
+    struct Payload {
+        to: String,
+        amount: f64,
+        memo: String,
+    }
+
+    let payload = Payload {
+        to: "b".to_string(),
+        amount: 10.0,
+        memo: "<é>".to_string(),
+    };
+
+    let expected = b"{\"amount\":10,\"memo\":\"<\xc3\xa9>\",\"to\":\"b\"}";
+    assert_eq!(to_canonical_vec(&payload).unwrap(), expected);
+    assert_eq!(
+        canonical("{ \"to\": \"b\", \"memo\": \"\\u003c\\u00e9>\", \"amount\": 1e1 }").as_bytes(),
+        expected
+    );
+
+    // the other options can't get in the way
+    let options = SerializeOptions::smart(10)
+        .ascii_only(true)
+        .escape_html(true)
+        .max_inline_depth(0);
+    let options = SerializeOptions {
+        canonical: true,
+        ..options
+    };
+    assert_eq!(
+        to_string_with(&payload, &options).unwrap().as_bytes(),
+        expected
+    );
+}
@@ -0,0 +1,20 @@
+/*
+    Helpers shared by the integration tests.
+*/
+
+/// Tiny xorshift generator, good enough to shuffle and mangle pieces of JSON.
+pub struct Rng(pub u64);
+
+impl Rng {
+    pub fn below(&mut self, max: usize) -> usize {
+        self.0 ^= self.0 << 13;
+        self.0 ^= self.0 >> 7;
+        self.0 ^= self.0 << 17;
+
+        (self.0 % max as u64) as usize
+    }
+
+    pub fn pick<'a, T>(&mut self, items: &'a [T]) -> &'a T {
+        &items[self.below(items.len())]
+    }
+}
@@ -0,0 +1,227 @@
+use std::fmt;
+
+use iovera::{
+    Dialect, Document, JsonSerialize, JsonValue, Node, NodeKind, Parser, SerializeError, Serializer,
+};
+
+const CONFIG: &str = r#"// settings of the server
+{
+    "name": "iovera",  // shown in the title
+    "port": 8080,
+    "tags": [ "a", "b" ],
+    /* nested */ "tls": {"cert": "x.pem", "key": "x.key"},
+}
+"#;
+
+fn config() -> Document {
+    Document::with_dialect(CONFIG, Dialect::Jsonc).unwrap()
+}
+
+// Every span of the tree has to cover exactly the text it stands for.
+fn assert_spans(document: &Document) {
+    fn walk(node: &Node, text: &str) {
+        let slice = node.span().slice(text).unwrap();
+        let parsed = Parser::with_dialect(slice, Dialect::Json5).parse().unwrap();
+        assert_eq!(parsed, node.to_value(), "{slice:?}");
+
+        match node.kind() {
+            NodeKind::Scalar(_) => {}
+
+            NodeKind::Object(members) => {
+                for member in members {
+                    let key = member.key_span().slice(text).unwrap();
+                    let parsed = Parser::with_dialect(key, Dialect::Json5).parse().unwrap();
+                    assert_eq!(parsed.as_str(), Some(member.key()));
+                    assert_eq!(member.colon().slice(text), Some(":"));
+                    assert!(
+                        member
+                            .comma()
+                            .is_none_or(|comma| comma.slice(text) == Some(","))
+                    );
+
+                    walk(member.value(), text);
+                }
+            }
+
+            NodeKind::Array(elements) => {
+                for element in elements {
+                    assert!(
+                        element
+                            .comma()
+                            .is_none_or(|comma| comma.slice(text) == Some(","))
+                    );
+                    walk(element.value(), text);
+                }
+            }
+        }
+    }
+
+    walk(document.root(), document.text());
+
+    let reparsed = Document::with_dialect(document.text(), Dialect::Jsonc).unwrap();
+    assert_eq!(reparsed.root(), document.root());
+}
+
+#[test]
+fn untouched_documents_print_byte_for_byte() {
+    let document = config();
+    assert_eq!(document.to_string(), CONFIG);
+    assert_eq!(
+        document.to_value(),
+        Parser::with_dialect(CONFIG, Dialect::Jsonc)
+            .parse()
+            .unwrap()
+    );
+    assert_spans(&document);
+
+    let json5 = "{unquoted: 'single', hex: 0x10, list: [1, 2,],}";
+    let document = Document::with_dialect(json5, Dialect::Json5).unwrap();
+    assert_eq!(document.text(), json5);
+    assert_eq!(document.get("/hex").unwrap().to_value().as_i64(), Some(16));
+}
+
+#[test]
+fn set_replaces_only_the_value() {
+    let mut document = config();
+
+    assert!(document.set("/port", &9090).unwrap());
+    assert!(document.set("/tls/cert", &JsonValue::Null).unwrap());
+    assert!(document.set("/tags/1", &vec![1, 2]).unwrap());
+
+    assert_eq!(
+        document.text(),
+        CONFIG
+            .replace("8080", "9090")
+            .replace("\"x.pem\"", "null")
+            .replace("\"b\"", "[1,2]")
+    );
+    assert_spans(&document);
+
+    // a missing key is added, a missing parent isn't
+    assert!(document.set("/tls/ca", "ca.pem").unwrap());
+    assert!(!document.set("/nowhere/ca", "ca.pem").unwrap());
+    assert!(!document.set("port", &1).unwrap());
+    assert_eq!(
+        document.get("/tls/ca").unwrap().to_value().as_str(),
+        Some("ca.pem")
+    );
+}
+
+#[test]
+fn insert_copies_the_layout_of_the_neighbours() {
+    let mut document = config();
+
+    assert!(document.insert("/debug", &true).unwrap());
+    assert!(document.insert("/tags/0", "first").unwrap());
+    assert!(document.insert("/tags/-", "last").unwrap());
+    assert!(document.insert("/tls/dh", "dh.pem").unwrap());
+    assert!(!document.insert("/port", &1).unwrap());
+    assert!(!document.insert("/tags/9", &1).unwrap());
+
+    let expected = r#"// settings of the server
+{
+    "name": "iovera",  // shown in the title
+    "port": 8080,
+    "tags": [ "first", "a", "b", "last" ],
+    /* nested */ "tls": {"cert": "x.pem", "key": "x.key", "dh": "dh.pem"},
+    "debug": true,
+}
+"#;
+    assert_eq!(document.text(), expected);
+    assert_spans(&document);
+
+    let mut document = Document::new("{\"empty\": {}, \"list\": []}").unwrap();
+    assert!(document.insert("/empty/a", &1).unwrap());
+    assert!(document.insert("/list/0", &2).unwrap());
+    assert_eq!(document.text(), "{\"empty\": {\"a\": 1}, \"list\": [2]}");
+    assert_spans(&document);
+
+    // in front of the first element the separator comes from the one after
+    for (text, expected) in [
+        ("[1, 2]", "[0, 1, 2]"),
+        ("[1,2]", "[0,1,2]"),
+        ("[1]", "[0, 1]"),
+        ("[\n  1\n]", "[\n  0,\n  1\n]"),
+    ] {
+        let mut document = Document::new(text).unwrap();
+        assert!(document.insert("/0", &0).unwrap());
+        assert_eq!(document.text(), expected);
+        assert_spans(&document);
+    }
+}
+
+// Fails as soon as it is written.
+struct Broken;
+
+impl JsonSerialize for Broken {
+    fn serialize(&self, _: &mut Serializer<'_>) -> fmt::Result {
+        Err(fmt::Error)
+    }
+}
+
+#[test]
+fn failed_writes_leave_the_document_alone() {
+    let mut document = config();
+
+    assert_eq!(document.set("/port", &Broken), Err(SerializeError::Write));
+    assert_eq!(document.set("/debug", &Broken), Err(SerializeError::Write));
+    assert_eq!(
+        document.insert("/tags/0", &Broken),
+        Err(SerializeError::Write)
+    );
+
+    assert_eq!(document.text(), CONFIG);
+    assert_spans(&document);
+}
+
+#[test]
+fn remove_takes_its_comma_and_comments_along() {
+    let mut document = config();
+
+    assert_eq!(document.remove("/name").unwrap().as_str(), Some("iovera"));
+    assert_eq!(document.remove("/tags/1").unwrap().as_str(), Some("b"));
+    assert_eq!(document.remove("/tls/key").unwrap().as_str(), Some("x.key"));
+    assert!(document.remove("/tls/key").is_none());
+    assert!(document.remove("").is_none());
+
+    let expected = r#"// settings of the server
+{  // shown in the title
+    "port": 8080,
+    "tags": [ "a" ],
+    /* nested */ "tls": {"cert": "x.pem"},
+}
+"#;
+    assert_eq!(document.text(), expected);
+    assert_spans(&document);
+
+    assert!(document.remove("/tls/cert").is_some());
+    assert!(document.remove("/tags/0").is_some());
+    assert!(document.remove("/tls").is_some());
+    assert_eq!(
+        document.text(),
+        "// settings of the server\n{  // shown in the title\n    \"port\": 8080,\n    \"tags\": [ ],\n}\n"
+    );
+    assert_spans(&document);
+}
+
+#[test]
+fn rename_keeps_position_and_value() {
+    let mut document = config();
+
+    assert!(document.rename("/port", "listen"));
+    assert!(document.rename("/tls/cert", "a/b~c"));
+    assert!(!document.rename("/name", "tags"));
+    assert!(!document.rename("/tags/0", "x"));
+
+    assert_eq!(
+        document.text(),
+        CONFIG
+            .replace("\"port\"", "\"listen\"")
+            .replace("\"cert\"", "\"a/b~c\"")
+    );
+    assert_eq!(
+        document.get("/tls/a~1b~0c").unwrap().to_value().as_str(),
+        Some("x.pem")
+    );
+    assert_spans(&document);
+}
@@ -0,0 +1,127 @@
+use std::collections::BTreeMap;
+
+use iovera::{ErrorKind, JsonDeserialize, JsonSerialize, from_str, to_string, to_writer};
+
+#[derive(Debug, PartialEq, JsonDeserialize)]
+pub struct Config {
+    name: String,
+    pub enabled: bool,
+    tags: Vec<String>,
+    nested: Option<Inner>,
+    /// documented field
+    r#type: std::string::String,
+}
+
+#[derive(Debug, PartialEq, JsonDeserialize)]
+pub(crate) struct Inner {
+    flag: bool,
+    depth: Option<Box<i64>>,
+}
+
+#[derive(Debug, JsonDeserialize)]
+struct Wrapper<T> {
+    inner: T,
+}
+
+#[test]
+fn reads_struct_field_by_field() {
+    let config: Config = from_str(
+        "{\"enabled\": true, \"name\": \"srv\", \"tags\": [\"a\", \"b\"], \
+         \"nested\": {\"flag\": true}, \"type\": \"t\"}",
+    )
+    .unwrap();
+
+    assert_eq!(
+        config,
+        Config {
+            name: "srv".to_string(),
+            enabled: true,
+            tags: vec!["a".to_string(), "b".to_string()],
+            nested: Some(Inner {
+                flag: true,
+                depth: None
+            }),
+            r#type: "t".to_string(),
+        }
+    );
+
+    let wrapper: Wrapper<Vec<Option<bool>>> = from_str("{\"inner\": [null, true]}").unwrap();
+    assert_eq!(wrapper.inner, vec![None, Some(true)]);
+
+    let wrapper: Wrapper<Vec<u8>> = from_str("{\"inner\": [1, 255]}").unwrap();
+    assert_eq!(wrapper.inner, vec![1, 255]);
+}
+
+#[test]
+fn reports_field_errors() {
+    let err = from_str::<Inner>("{\"depth\": null, \"flag\": true, \"extra\": null}").unwrap_err();
+    assert_eq!(err.kind(), &ErrorKind::UnknownField("extra".to_owned()));
+    assert_eq!(err.span().as_tuple(), (30, 37));
+
+    let err = from_str::<Inner>("{\"depth\": null}").unwrap_err();
+    assert_eq!(err.kind(), &ErrorKind::MissingField("flag"));
+    assert_eq!(err.span().as_tuple(), (0, 1));
+
+    let err = from_str::<Inner>("{\"flag\": \"yes\"}").unwrap_err();
+    assert!(matches!(err.kind(), ErrorKind::UnexpectedToken { .. }));
+
+    let err = from_str::<Wrapper<u8>>("{\"inner\": 256}").unwrap_err();
+    assert_eq!(err.kind(), &ErrorKind::OutOfRange("u8"));
+
+    // integers beyond `i64` are out of range rather than not numbers at all
+    let err = from_str::<Wrapper<i64>>("{\"inner\": 9223372036854775808}").unwrap_err();
+    assert_eq!(err.kind(), &ErrorKind::OutOfRange("i64"));
+
+    let err = from_str::<Wrapper<u64>>("{\"inner\": 1.5}").unwrap_err();
+    assert!(matches!(err.kind(), ErrorKind::UnexpectedToken { .. }));
+}
+
+#[derive(Debug, PartialEq, JsonSerialize, JsonDeserialize)]
+struct Counter {
+    count: u64,
+    offset: i64,
+}
+
+#[test]
+fn integers_round_trip_whole() {
+    for (count, offset) in [(u64::MAX, i64::MIN), (0, i64::MAX), (1 << 63, -1)] {
+        let counter = Counter { count, offset };
+        let text = to_string(&counter);
+
+        assert_eq!(from_str::<Counter>(&text).unwrap(), counter, "{text}");
+    }
+
+    assert_eq!(from_str::<u64>("18446744073709551615").unwrap(), u64::MAX);
+    assert_eq!(
+        from_str::<u64>("18446744073709551616").unwrap_err().kind(),
+        &ErrorKind::OutOfRange("u64")
+    );
+}
+
+#[derive(JsonSerialize)]
+struct Report<'a, T> {
+    title: &'a str,
+    scores: Vec<T>,
+    extra: Option<BTreeMap<String, bool>>,
+    r#ref: Option<u8>,
+}
+
+#[test]
+fn writes_struct_member_by_member() {
+    let report = Report {
+        title: "quote \" and\nnewline",
+        scores: vec![1.5, -2.0],
+        extra: Some(BTreeMap::from([("k".to_string(), true)])),
+        r#ref: None,
+    };
+
+    let expected = concat!(
+        "{\"title\":\"quote \\\" and\\nnewline\",\"scores\":[1.5,-2.0],",
+        "\"extra\":{\"k\":true},\"ref\":null}"
+    );
+    assert_eq!(to_string(&report), expected);
+
+    let mut bytes = Vec::new();
+    to_writer(&mut bytes, &report).unwrap();
+    assert_eq!(bytes, expected.as_bytes());
+}
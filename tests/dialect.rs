@@ -0,0 +1,73 @@
+use iovera::{Dialect, JsonValue, Parser};
+
+fn parse(input: &str, dialect: Dialect) -> Result<JsonValue, iovera::Error> {
+    Parser::with_dialect(input, dialect).parse()
+}
+
+#[test]
+fn jsonc() {
+    let input = "// config\n{\n  \"a\": [1, 2,], /* inline */\n  \"b\": {\"c\": null,},\n}\n";
+    let value = parse(input, Dialect::Jsonc).unwrap();
+
+    assert_eq!(value["a"][1].as_i64(), Some(2));
+    assert!(value["b"]["c"].is_null());
+
+    // strict stays the default
+    assert!(input.parse::<JsonValue>().is_err());
+    assert!(parse("[1,]", Dialect::Strict).is_err());
+
+    for bad in ["[1,,]", "[,]", "{,}", "[1] /* open", "[1 /2]"] {
+        assert!(parse(bad, Dialect::Jsonc).is_err(), "{bad}");
+    }
+
+    // JSON5 only additions
+    for bad in ["{a: 1}", "[0x10]", "[+1]", "[NaN]"] {
+        assert!(parse(bad, Dialect::Jsonc).is_err(), "{bad}");
+    }
+}
+
+#[test]
+fn json5() {
+    let input = concat!(
+        "{\n",
+        "  unquoted: 'single \"quoted\"',\n",
+        "  $_id2: \"multi\\\n",
+        "line\",\n",
+        "  null: [0xFF, -0x10, +1, .5, 5., +Infinity, -Infinity],\n",
+        "  escapes: '\\x41\\v\\0\\'\\q',\n",
+        "}",
+    );
+    let value = parse(input, Dialect::Json5).unwrap();
+
+    assert_eq!(value["unquoted"].as_str(), Some("single \"quoted\""));
+    assert_eq!(value["$_id2"].as_str(), Some("multiline"));
+    assert_eq!(value["escapes"].as_str(), Some("A\u{0B}\0'q"));
+
+    let nums = &value["null"];
+    assert_eq!(nums[0].as_i64(), Some(255));
+    assert_eq!(nums[1].as_i64(), Some(-16));
+    assert_eq!(nums[2].as_i64(), Some(1));
+    assert_eq!(nums[3].as_f64(), Some(0.5));
+    assert_eq!(nums[4].as_f64(), Some(5.0));
+    assert_eq!(nums[5].as_f64(), Some(f64::INFINITY));
+    assert_eq!(nums[6].as_f64(), Some(f64::NEG_INFINITY));
+    assert!(
+        parse("NaN", Dialect::Json5)
+            .unwrap()
+            .as_f64()
+            .unwrap()
+            .is_nan()
+    );
+
+    for bad in [
+        "[ident]",
+        "{1: 2}",
+        "[0x]",
+        "[01]",
+        "'a\nb'",
+        "[.]",
+        "{-Infinity: 1}",
+    ] {
+        assert!(parse(bad, Dialect::Json5).is_err(), "{bad:?}");
+    }
+}
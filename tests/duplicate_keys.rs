@@ -0,0 +1,117 @@
+use std::collections::BTreeMap;
+
+use iovera::{DuplicateKeys, ErrorKind, JsonDeserialize, JsonValue, ParseOptions, Parser};
+
+#[derive(Debug, PartialEq, JsonDeserialize)]
+struct Point {
+    x: i64,
+    y: i64,
+}
+
+const INPUT: &str = "{\"x\": 1, \"y\": 2, \"x\": 3}";
+
+fn options(policy: DuplicateKeys) -> ParseOptions {
+    ParseOptions::new().duplicate_keys(policy)
+}
+
+#[test]
+fn error_reports_both_keys() {
+    let err = Parser::with_options(INPUT, options(DuplicateKeys::Error))
+        .parse()
+        .unwrap_err();
+
+    let ErrorKind::DuplicateKey { ref key, first } = *err.kind() else {
+        panic!("unexpected error {err}");
+    };
+    assert_eq!(key, "x");
+    assert_eq!(first.slice(INPUT), Some("\"x\""));
+    assert_eq!(err.span().as_tuple(), (17, 20));
+
+    let err = Parser::with_options(INPUT, options(DuplicateKeys::Error))
+        .deserialize::<Point>()
+        .unwrap_err();
+    assert!(matches!(err.kind(), ErrorKind::DuplicateKey { .. }));
+    assert_eq!(err.span().as_tuple(), (17, 20));
+
+    // the same key in different objects is fine
+    let nested = "{\"x\": {\"x\": 1}, \"y\": [{\"x\": 2}, {\"x\": 3}]}";
+    assert!(
+        Parser::with_options(nested, options(DuplicateKeys::Error))
+            .parse()
+            .is_ok()
+    );
+}
+
+#[test]
+fn first_or_last_wins() {
+    let value = Parser::with_options(INPUT, options(DuplicateKeys::FirstWins))
+        .parse()
+        .unwrap();
+    assert_eq!(value["x"].as_i64(), Some(1));
+
+    let point: Point = Parser::with_options(INPUT, options(DuplicateKeys::FirstWins))
+        .deserialize()
+        .unwrap();
+    assert_eq!(point, Point { x: 1, y: 2 });
+
+    let map: BTreeMap<String, i64> = Parser::with_options(INPUT, options(DuplicateKeys::FirstWins))
+        .deserialize()
+        .unwrap();
+    assert_eq!(map["x"], 1);
+
+    // skipped values are still checked
+    let err = Parser::with_options("{\"x\": 1, \"x\": [}", options(DuplicateKeys::FirstWins))
+        .parse()
+        .unwrap_err();
+    assert!(matches!(err.kind(), ErrorKind::UnexpectedToken { .. }));
+
+    let value = INPUT.parse::<JsonValue>().unwrap();
+    assert_eq!(value["x"].as_i64(), Some(3));
+
+    let point: Point = Parser::with_options(INPUT, options(DuplicateKeys::LastWins))
+        .deserialize()
+        .unwrap();
+    assert_eq!(point, Point { x: 3, y: 2 });
+}
+
+#[test]
+fn keep_all_gives_a_multi_map() {
+    let value = Parser::with_options(INPUT, options(DuplicateKeys::KeepAll))
+        .parse()
+        .unwrap();
+    let map = value.as_object().unwrap();
+
+    assert_eq!(map.len(), 2);
+    assert_eq!(map.get("x"), Some(&3.into()));
+
+    let multi = map.multi();
+    assert_eq!(multi.len(), 3);
+    assert!(multi.has_duplicates());
+
+    let all: Vec<_> = multi.get_all("x").filter_map(JsonValue::as_i64).collect();
+    assert_eq!(all, [1, 3]);
+    assert_eq!(multi.get_all("z").count(), 0);
+
+    let entries: Vec<_> = multi
+        .iter()
+        .map(|(key, value)| (key.as_str(), value.as_i64().unwrap()))
+        .collect();
+    assert_eq!(entries, [("x", 1), ("x", 3), ("y", 2)]);
+
+    let point: Point = Parser::with_options(INPUT, options(DuplicateKeys::KeepAll))
+        .deserialize()
+        .unwrap();
+    assert_eq!(point, Point { x: 3, y: 2 });
+}
+
+#[test]
+fn duplicates_leave_with_their_key() {
+    let mut value = Parser::with_options(INPUT, options(DuplicateKeys::KeepAll))
+        .parse()
+        .unwrap();
+    let map = value.as_object_mut().unwrap();
+
+    assert_eq!(map.remove("x"), Some(3.into()));
+    assert!(!map.multi().has_duplicates());
+    assert_eq!(map.multi().len(), 1);
+}
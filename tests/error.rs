@@ -0,0 +1,56 @@
+use iovera::{ErrorKind, JsonValue};
+
+#[test]
+fn line_and_column() {
+    let input = "{\n  \"a\": 1,\n  \"b\": }";
+    let err = input.parse::<JsonValue>().unwrap_err();
+
+    assert_eq!(
+        err.kind(),
+        &ErrorKind::UnexpectedToken {
+            found: "`}`",
+            expected: "a value"
+        }
+    );
+    assert_eq!((err.line(), err.column()), (3, 8));
+    assert_eq!(
+        err.to_string(),
+        "expected a value, found `}` at line 3, column 8"
+    );
+
+    let snippet = err.snippet(input).to_string();
+    assert_eq!(
+        snippet,
+        concat!(
+            "error: expected a value, found `}`\n",
+            " --> line 3, column 8\n",
+            "  |\n",
+            "3 |   \"b\": }\n",
+            "  |        ^",
+        )
+    );
+}
+
+#[test]
+fn lexer_error_kinds() {
+    let cases = [
+        (
+            "[01]",
+            ErrorKind::InvalidNumber("numbers can't have leading zeros"),
+        ),
+        (
+            "\"\\x\"",
+            ErrorKind::InvalidEscape("unknown escape character"),
+        ),
+        ("\"\\uDC00\"", ErrorKind::LoneSurrogate),
+        ("\"a\tb\"", ErrorKind::ControlCharacter('\t')),
+        ("[nope]", ErrorKind::InvalidLiteral),
+        ("\"open", ErrorKind::UnexpectedEof("a closing `\"`")),
+        ("[1,", ErrorKind::UnexpectedEof("a value")),
+    ];
+
+    for (input, kind) in cases {
+        let err = input.parse::<JsonValue>().unwrap_err();
+        assert_eq!(err.kind(), &kind, "{input:?}");
+    }
+}
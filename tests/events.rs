@@ -0,0 +1,56 @@
+use iovera::{Dialect, ErrorKind, Event, Events, Number};
+
+#[test]
+fn events_with_spans_and_depth() {
+    let input = r#"{"a": [1, {"b": null}], "c": "d"}"#;
+    let events = Events::new(input).collect::<Result<Vec<_>, _>>().unwrap();
+
+    let expected = [
+        (Event::StartObject, "{", 0),
+        (Event::Key("a".into()), "\"a\"", 1),
+        (Event::StartArray, "[", 1),
+        (Event::Number(Number::Int(1)), "1", 2),
+        (Event::StartObject, "{", 2),
+        (Event::Key("b".into()), "\"b\"", 3),
+        (Event::Null, "null", 3),
+        (Event::EndObject, "}", 2),
+        (Event::EndArray, "]", 1),
+        (Event::Key("c".into()), "\"c\"", 1),
+        (Event::String("d".into()), "\"d\"", 1),
+        (Event::EndObject, "}", 0),
+    ];
+
+    assert_eq!(events.len(), expected.len());
+    for (spanned, (event, text, depth)) in events.into_iter().zip(expected) {
+        assert_eq!(spanned.event, event);
+        assert_eq!(spanned.span.slice(input), Some(text));
+        assert_eq!(spanned.depth, depth);
+    }
+}
+
+#[test]
+fn grammar_is_enforced() {
+    let bad = [
+        "{1: 2}",
+        "[1 2]",
+        "[1,]",
+        "{\"a\" 1}",
+        "{\"a\": 1,}",
+        "[}",
+        "{]",
+        "1 2",
+        "[",
+        "",
+    ];
+
+    for input in bad {
+        let result = Events::new(input).collect::<Result<Vec<_>, _>>();
+        assert!(result.is_err(), "{input:?}");
+    }
+
+    let err = Events::new("[1, 2").find_map(Result::err).unwrap();
+    assert_eq!(err.kind(), &ErrorKind::UnexpectedEof("`,` or `]`"));
+
+    let events = Events::with_dialect("{a: [1,],}", Dialect::Json5).count();
+    assert_eq!(events, 6);
+}
@@ -0,0 +1,154 @@
+use iovera::{Dialect, Document, ParseOptions};
+
+mod common;
+use common::Rng;
+
+const TEXT: &str = r#"{
+    "servers": [
+        {"host": "a.example", "ports": [80, 443]},
+        {"host": "b.example", "ports": []} // backup
+    ],
+    "retries": 3
+}"#;
+
+fn document() -> Document {
+    Document::with_dialect(TEXT, Dialect::Jsonc).unwrap()
+}
+
+// Applies the edit to the document and checks it against parsing the edited text from scratch.
+fn assert_edit(document: &mut Document, start: usize, end: usize, replacement: &str) {
+    let mut text = document.text().to_owned();
+    text.replace_range(start..end, replacement);
+
+    let before = document.clone();
+    let result = document.edit(start, end, replacement);
+
+    match Document::with_options(text.as_str(), *document.options()) {
+        Ok(expected) => {
+            assert!(result.is_ok(), "{text:?}: {result:?}");
+            assert_eq!(document.text(), text);
+            assert_eq!(document.root(), expected.root(), "{text:?}");
+        }
+
+        Err(err) => {
+            let found = result.expect_err(&text);
+            assert_eq!(found.kind(), err.kind(), "{text:?}");
+            assert_eq!(found.span(), err.span(), "{text:?}");
+
+            // nothing changed
+            assert_eq!(document.text(), before.text());
+            assert_eq!(document.root(), before.root());
+        }
+    }
+}
+
+#[test]
+fn only_the_innermost_container_is_reparsed() {
+    let mut document = document();
+
+    // inside the `ports` of the first server
+    let at = TEXT.find("443").unwrap();
+    let ports = document.get("/servers/0/ports").unwrap().span();
+    let reparsed = document.edit(at, at + 3, "8443, 9443").unwrap();
+    assert_eq!(reparsed.as_tuple(), (ports.start(), ports.end() + 7));
+
+    // the nodes after the edit moved along
+    let retries = document.get("/retries").unwrap().span();
+    assert_eq!(retries.slice(document.text()), Some("3"));
+    assert_eq!(
+        document
+            .get("/servers/0/ports/2")
+            .unwrap()
+            .to_value()
+            .as_i64(),
+        Some(9443)
+    );
+
+    // replacing the whole `ports` array reparses the server around it
+    let ports = document.get("/servers/1/ports").unwrap().span();
+    let server = document.get("/servers/1").unwrap().span();
+    let reparsed = document.edit(ports.start(), ports.end(), "{}").unwrap();
+    assert_eq!(reparsed.as_tuple(), (server.start(), server.end()));
+    assert!(
+        document
+            .get("/servers/1/ports")
+            .unwrap()
+            .to_value()
+            .as_object()
+            .is_some()
+    );
+
+    // touching the root brackets reparses everything
+    let len = document.text().len();
+    let reparsed = document.edit(len, len, "\n").unwrap();
+    assert_eq!(reparsed.as_tuple(), (0, len + 1));
+}
+
+#[test]
+fn edits_spilling_out_of_a_container_go_up() {
+    let mut document = Document::new("[{\"a\": 1}, 2]").unwrap();
+
+    // the object no longer parses on its own, the array does
+    let reparsed = document.edit(7, 8, "1}, {\"b\": 2").unwrap();
+    assert_eq!(reparsed.as_tuple(), (0, document.text().len()));
+    assert_eq!(document.text(), "[{\"a\": 1}, {\"b\": 2}, 2]");
+    assert_eq!(document.get("/1/b").unwrap().to_value().as_i64(), Some(2));
+}
+
+#[test]
+fn broken_edits_leave_the_document_alone() {
+    let mut document = document();
+    let at = TEXT.find("80").unwrap();
+
+    let err = document.edit(at, at + 2, "8 0").unwrap_err();
+    assert_eq!(document.text(), TEXT);
+    assert_eq!(err.span().as_tuple(), (at + 2, at + 3));
+    assert_eq!((err.line(), err.column()), (3, 43));
+
+    // the limits hold for reparsed containers too
+    let options = ParseOptions::new().max_depth(3);
+    let mut document = Document::with_options("[[[]], 1]", options).unwrap();
+    assert!(document.edit(3, 3, "[]").is_err());
+    assert_eq!(document.text(), "[[[]], 1]");
+}
+
+#[test]
+fn random_edits_match_parsing_from_scratch() {
+    const PIECES: &[&str] = &[
+        "",
+        "1",
+        "-2.5",
+        "\"x\"",
+        "\"",
+        "true",
+        ",",
+        ":",
+        "{",
+        "}",
+        "[",
+        "]",
+        " ",
+        "\n",
+        "[1, 2]",
+        "{\"k\": null}",
+        ", \"k\": 1",
+        "// c\n",
+        "/* c */",
+        "\\",
+    ];
+
+    let mut rng = Rng(0x853C_49E6_748F_EA9B);
+
+    for _ in 0..200 {
+        let mut document = document();
+
+        for _ in 0..20 {
+            let len = document.text().len();
+            let start = rng.below(len + 1);
+            let end = (start + rng.below(6)).min(len);
+
+            // the text stays ASCII, every offset is a character boundary
+            assert_edit(&mut document, start, end, rng.pick(PIECES));
+        }
+    }
+}
@@ -0,0 +1,38 @@
+use iovera::{Lexer, Token};
+
+#[test]
+fn tokens_are_lexed_on_demand() {
+    // the garbage at the end is only reached once asked for
+    let mut lexer = Lexer::new("[1, \"two\" @");
+
+    assert!(matches!(lexer.peek(1), Ok(Some(Token::Int(1, _)))));
+    assert!(matches!(
+        lexer.next(),
+        Some(Ok(Token::LeftSquareBracket(_)))
+    ));
+    assert!(matches!(lexer.next(), Some(Ok(Token::Int(1, _)))));
+    assert!(matches!(lexer.next(), Some(Ok(Token::Comma(_)))));
+    assert!(matches!(lexer.peek(0), Ok(Some(Token::String(..)))));
+    assert!(matches!(lexer.next(), Some(Ok(Token::String(..)))));
+}
+
+#[test]
+fn errors_met_by_peek_come_out_of_next() {
+    let mut lexer = Lexer::new("[1 @");
+
+    assert!(lexer.peek(2).is_err());
+    assert!(lexer.peek(3).is_err());
+    assert!(matches!(
+        lexer.next(),
+        Some(Ok(Token::LeftSquareBracket(_)))
+    ));
+    assert!(matches!(lexer.next(), Some(Ok(Token::Int(1, _)))));
+    assert!(matches!(lexer.next(), Some(Err(_))));
+    assert!(lexer.next().is_none());
+}
+
+#[test]
+fn eager_tokenize() {
+    let tokens = Lexer::tokenize("{\"a\": null}").unwrap();
+    assert_eq!(tokens.len(), 5);
+}
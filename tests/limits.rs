@@ -0,0 +1,134 @@
+use std::io::{self, Read};
+
+use iovera::{ErrorKind, Events, JsonValue, ParseOptions, Parser, Progress, PushParser, ReadLexer};
+
+// Runs `input` through the tree parser, the event iterator and the push parser,
+// checking all of them fail with `kind` at the same span.
+fn assert_limit(input: &str, options: ParseOptions, kind: ErrorKind, span: (usize, usize)) {
+    let err = Parser::with_options(input, options).parse().unwrap_err();
+    assert_eq!(
+        (err.kind(), err.span().as_tuple()),
+        (&kind, span),
+        "parser: {input}"
+    );
+
+    let err = Events::with_options(input, options)
+        .find_map(Result::err)
+        .unwrap();
+    assert_eq!(
+        (err.kind(), err.span().as_tuple()),
+        (&kind, span),
+        "events: {input}"
+    );
+
+    let mut parser = PushParser::with_options(options);
+    let err = parser
+        .feed(input.as_bytes())
+        .and_then(|()| parser.finish())
+        .and_then(|()| {
+            loop {
+                if let Progress::Done = parser.next_event()? {
+                    break Ok(());
+                }
+            }
+        })
+        .unwrap_err();
+    assert_eq!(
+        (err.kind(), err.span().as_tuple()),
+        (&kind, span),
+        "push: {input}"
+    );
+}
+
+#[test]
+fn each_limit_has_its_own_error() {
+    let options = ParseOptions::unlimited();
+
+    assert_limit(
+        "[[[1]]]",
+        options.max_depth(2),
+        ErrorKind::DepthLimitExceeded(2),
+        (2, 3),
+    );
+    assert_limit(
+        "[\"abc\", \"abcd\"]",
+        options.max_string_len(3),
+        ErrorKind::StringTooLong(3),
+        (8, 13),
+    );
+    assert_limit(
+        "{\"ab\": 1, \"abc\": 2}",
+        options.max_key_len(2),
+        ErrorKind::KeyTooLong(2),
+        (10, 15),
+    );
+    assert_limit(
+        "[1, 2, 3]",
+        options.max_tokens(4),
+        ErrorKind::TooManyTokens(4),
+        (5, 6),
+    );
+    assert_limit(
+        "{\"a\": [1, 2], \"b\": 3}",
+        options.max_members(1),
+        ErrorKind::TooManyMembers(1),
+        (10, 11),
+    );
+    assert_limit(
+        "[1, 2]",
+        options.max_input_len(4),
+        ErrorKind::InputTooLarge(4),
+        (4, 6),
+    );
+}
+
+#[test]
+fn default_depth_limit() {
+    let deep = "[".repeat(200) + &"]".repeat(200);
+    let err = deep.parse::<JsonValue>().unwrap_err();
+    assert_eq!(err.kind(), &ErrorKind::DepthLimitExceeded(128));
+
+    let shallow = "[".repeat(100) + &"]".repeat(100);
+    assert!(shallow.parse::<JsonValue>().is_ok());
+}
+
+#[test]
+fn unterminated_strings_stop_at_the_limit_when_streamed() {
+    let options = ParseOptions::new().max_string_len(16);
+
+    // a string that never ends
+    let endless = b"[\"".chain(io::repeat(b'a'));
+    let mut lexer = ReadLexer::with_options(endless, options);
+    lexer.set_chunk_size(7);
+
+    assert!(matches!(lexer.next(), Some(Ok(_))));
+    let err = lexer.next().unwrap().unwrap_err();
+    assert_eq!(
+        (err.kind(), err.span().as_tuple()),
+        (&ErrorKind::StringTooLong(16), (1, 19))
+    );
+
+    let mut parser = PushParser::with_options(options);
+    let mut fed = parser.feed(b"[\"");
+    let mut pushes = 0;
+
+    let err = loop {
+        match fed.and_then(|()| parser.next_event()) {
+            Ok(Progress::NeedMore) => {
+                assert!(pushes < 100, "still waiting after {pushes} pushes");
+                pushes += 1;
+                fed = parser.feed(b"aaaaa");
+            }
+
+            Ok(_) => fed = Ok(()),
+            Err(err) => break err,
+        }
+    };
+    assert_eq!(err.kind(), &ErrorKind::StringTooLong(16));
+
+    // escapes count as what they decode to
+    let escaped = format!("[\"{}\"]", "\\u0041\\n".repeat(8));
+    let mut lexer = ReadLexer::with_options(escaped.as_bytes(), options);
+    lexer.set_chunk_size(3);
+    assert!(lexer.all(|token| token.is_ok()));
+}
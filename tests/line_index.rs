@@ -0,0 +1,62 @@
+use iovera::{Encoding, Lexer, LineCol, LineIndex, Parser};
+
+#[test]
+fn positions_round_trip() {
+    // `é` is two bytes and one UTF-16 unit, `𝄞` four bytes and two units
+    let source = "{\n  \"é𝄞\": 1,\r\n  \"b\": [true]\n}";
+    let index = LineIndex::new(source);
+    assert_eq!(index.line_count(), 4);
+
+    let tokens = Lexer::tokenize(source).unwrap();
+    let one = *tokens[3].span();
+    assert_eq!(one.slice(source), Some("1"));
+
+    assert_eq!(
+        index.position(one.start(), Encoding::Utf8),
+        LineCol::new(1, 12)
+    );
+    assert_eq!(
+        index.position(one.start(), Encoding::Utf16),
+        LineCol::new(1, 9)
+    );
+    assert_eq!(
+        index.position(one.start(), Encoding::Chars),
+        LineCol::new(1, 8)
+    );
+
+    for span in tokens.iter().map(|token| *token.span()) {
+        for encoding in [Encoding::Utf8, Encoding::Utf16, Encoding::Chars] {
+            let (start, end) = index.span_position(span, encoding);
+            assert_eq!(index.span(start, end, encoding), Some(span));
+        }
+    }
+
+    // inside `𝄞`, past the end of a line and past the last line
+    assert_eq!(index.offset(LineCol::new(1, 5), Encoding::Utf16), None);
+    assert_eq!(index.offset(LineCol::new(3, 2), Encoding::Utf8), None);
+    assert_eq!(index.offset(LineCol::new(4, 0), Encoding::Utf8), None);
+}
+
+#[test]
+fn span_helpers() {
+    let source = "[1, 22]";
+    let tokens = Lexer::tokenize(source).unwrap();
+    let open = *tokens[0].span();
+    let close = *tokens[4].span();
+
+    let whole = open.join(close);
+    assert_eq!(whole.as_tuple(), (0, 7));
+    assert_eq!(whole.slice(source), Some(source));
+    assert_eq!(tokens[3].span().len(), 2);
+    assert_eq!(whole.slice("[1"), None);
+}
+
+#[test]
+fn errors_count_columns_in_chars() {
+    let source = "{\n  \"é𝄞\": @}";
+    let err = Parser::new(source).parse().unwrap_err();
+    let pos = LineIndex::new(source).position(err.span().start(), Encoding::Chars);
+
+    assert_eq!((err.line(), err.column()), (pos.line + 1, pos.column + 1));
+    assert_eq!((err.line(), err.column()), (2, 9));
+}
@@ -0,0 +1,86 @@
+use iovera::{JsonValue, Map, ParseOptions, Parser, to_string};
+
+const INPUT: &str = "{\"name\": \"srv\", \"port\": 80, \"debug\": false, \"addr\": null}";
+
+fn keys(map: &Map) -> Vec<&str> {
+    map.keys().map(String::as_str).collect()
+}
+
+#[test]
+fn keeps_the_order_of_the_source() {
+    let value: JsonValue = INPUT.parse().unwrap();
+    let map = value.as_object().unwrap();
+
+    assert_eq!(keys(map), ["name", "port", "debug", "addr"]);
+    assert_eq!(map.index_of("debug"), Some(2));
+    assert_eq!(map.get_index(1), Some((&"port".to_owned(), &80.into())));
+    assert_eq!(
+        to_string(&value),
+        INPUT.replace(": ", ":").replace(", ", ",")
+    );
+}
+
+#[test]
+fn editing_keeps_the_order() {
+    let mut value: JsonValue = INPUT.parse().unwrap();
+    let map = value.as_object_mut().unwrap();
+
+    // replacing a value leaves it in place, new keys go last
+    map.insert("port", 8080);
+    map.insert("tls", true);
+    assert_eq!(keys(map), ["name", "port", "debug", "addr", "tls"]);
+
+    assert_eq!(map.remove("port"), Some(8080.into()));
+    assert_eq!(keys(map), ["name", "debug", "addr", "tls"]);
+    assert_eq!(map.index_of("tls"), Some(3));
+
+    map.move_index(3, 0);
+    assert_eq!(keys(map), ["tls", "name", "debug", "addr"]);
+    map.move_index(1, 3);
+    assert_eq!(keys(map), ["tls", "debug", "addr", "name"]);
+    assert_eq!(map.index_of("name"), Some(3));
+
+    assert_eq!(map.swap_remove("tls"), Some(true.into()));
+    assert_eq!(keys(map), ["name", "debug", "addr"]);
+    assert_eq!(map.get("name"), Some(&"srv".into()));
+
+    map.sort_keys();
+    assert_eq!(keys(map), ["addr", "debug", "name"]);
+    assert_eq!(map.index_of("name"), Some(2));
+
+    map.sort_by(|_, a, _, b| a.is_null().cmp(&b.is_null()));
+    assert_eq!(keys(map), ["debug", "name", "addr"]);
+
+    *map.entry("debug").or_null() = true.into();
+    map.entry("level").or_insert(3);
+    assert_eq!(keys(map), ["debug", "name", "addr", "level"]);
+    assert_eq!(map.get("debug"), Some(&true.into()));
+}
+
+#[test]
+fn sorted_maps() {
+    let mut map = Map::sorted();
+    map.insert("b", 1);
+    map.insert("c", 2);
+    map.insert("a", 3);
+    assert_eq!(keys(&map), ["a", "b", "c"]);
+    assert_eq!(map.index_of("c"), Some(2));
+
+    let options = ParseOptions::new().sorted_maps(true);
+    let value = Parser::with_options(INPUT, options).parse().unwrap();
+    let mut map = value.as_object().unwrap().clone();
+
+    assert!(map.is_sorted());
+    assert_eq!(keys(&map), ["addr", "debug", "name", "port"]);
+
+    map.insert("host", "localhost");
+    assert_eq!(keys(&map), ["addr", "debug", "host", "name", "port"]);
+
+    // moving entries by hand gives up on the order
+    map.move_index(0, 4);
+    assert!(!map.is_sorted());
+
+    // order doesn't matter to equality
+    let unsorted: JsonValue = INPUT.parse().unwrap();
+    assert_eq!(unsorted, value);
+}
@@ -0,0 +1,96 @@
+use iovera::{Dialect, ErrorKind, Event, Number, Progress, PushParser};
+
+// Feeds `input` `size` bytes at a time, draining the events after each piece.
+fn events(
+    input: &[u8],
+    size: usize,
+    dialect: Dialect,
+) -> Result<Vec<Event<'static>>, iovera::Error> {
+    let mut parser = PushParser::with_dialect(dialect);
+    let mut events = Vec::new();
+
+    for piece in input.chunks(size) {
+        parser.feed(piece)?;
+
+        while let Progress::Event(event) = parser.next_event()? {
+            events.push(event.event);
+        }
+    }
+
+    parser.finish()?;
+
+    loop {
+        match parser.next_event()? {
+            Progress::Event(event) => events.push(event.event),
+            Progress::Done => return Ok(events),
+            Progress::NeedMore => panic!("more input asked for after `finish`"),
+        }
+    }
+}
+
+#[test]
+fn resumes_anywhere() {
+    let input = "{\"ünï\": [1, -2.5e3, \"a\\u0062\"], \"b\": {\"c\": [true, false, null]}} ";
+    let expected = events(input.as_bytes(), input.len(), Dialect::Strict).unwrap();
+
+    assert_eq!(expected[0], Event::StartObject);
+    assert_eq!(expected[1], Event::Key("ünï".into()));
+    assert_eq!(expected[4], Event::Number(Number::Float(-2500.0)));
+    assert_eq!(expected[5], Event::String("ab".into()));
+    assert_eq!(expected.len(), 17);
+
+    for size in 1..8 {
+        assert_eq!(
+            events(input.as_bytes(), size, Dialect::Strict).unwrap(),
+            expected
+        );
+    }
+}
+
+#[test]
+fn needs_more_until_finished() {
+    let mut parser = PushParser::new();
+
+    // the event of a completed step, leaving out its span and depth
+    let next = |parser: &mut PushParser| match parser.next_event().unwrap() {
+        Progress::Event(event) => Some(event.event),
+        Progress::NeedMore => None,
+        Progress::Done => panic!("done too early"),
+    };
+
+    parser.feed(b"[12").unwrap();
+    assert_eq!(next(&mut parser), Some(Event::StartArray));
+    assert_eq!(next(&mut parser), None);
+    assert_eq!(parser.depth(), 1);
+
+    parser.feed(b"3]").unwrap();
+    assert_eq!(next(&mut parser), Some(Event::Number(Number::Int(123))));
+    assert_eq!(next(&mut parser), Some(Event::EndArray));
+    assert_eq!(next(&mut parser), None);
+
+    parser.finish().unwrap();
+    assert_eq!(parser.next_event().unwrap(), Progress::Done);
+}
+
+#[test]
+fn grammar_errors() {
+    let cases = [
+        ("[1 2]", "`,` or `]`"),
+        ("{\"a\" 1}", "`:`"),
+        ("{\"a\": 1,}", "a string key"),
+        ("[1] 2", "end of input after the root value"),
+    ];
+
+    for (input, expected) in cases {
+        let err = events(input.as_bytes(), 1, Dialect::Strict).unwrap_err();
+        assert!(
+            matches!(err.kind(), ErrorKind::UnexpectedToken { expected: e, .. } if *e == expected),
+            "{input}: {err}"
+        );
+    }
+
+    let err = events(b"{\"a\": [1,", 2, Dialect::Strict).unwrap_err();
+    assert_eq!(err.kind(), &ErrorKind::UnexpectedEof("a value"));
+
+    assert!(events(b"{\"a\": [1,],}", 3, Dialect::Jsonc).is_ok());
+}
@@ -0,0 +1,86 @@
+use iovera::{Dialect, ErrorKind, Lexer, ReadLexer, Token};
+
+// Debug output of the tokens, spans included, to compare both lexers by.
+fn debug_tokens<'t>(tokens: impl Iterator<Item = Token<'t>>) -> Vec<String> {
+    tokens.map(|token| format!("{token:?}")).collect()
+}
+
+#[test]
+fn tokens_across_chunk_boundaries() {
+    let input = "{\"ключ\": [12345, -0.5e10, true, null, \"a\\u00e9\\\"b\"],\n \"x\": false}";
+    let expected = debug_tokens(Lexer::tokenize(input).unwrap().into_iter());
+
+    for chunk_size in 1..8 {
+        let mut lexer = ReadLexer::new(input.as_bytes());
+        lexer.set_chunk_size(chunk_size);
+
+        let tokens = lexer.collect::<Result<Vec<_>, _>>().unwrap();
+        assert_eq!(debug_tokens(tokens.into_iter()), expected, "{chunk_size}");
+    }
+}
+
+#[test]
+fn comments_across_chunk_boundaries() {
+    let input = "[1, // one\n /* two */ 2]";
+    let mut lexer = ReadLexer::with_dialect(input.as_bytes(), Dialect::Jsonc);
+    lexer.set_chunk_size(3);
+
+    assert_eq!(lexer.count(), 5);
+}
+
+#[test]
+fn long_strings_and_trivia_in_small_chunks() {
+    // each chunk only looks at what's new, these would take ages otherwise
+    let long = "ab\\\"é".repeat(1 << 20);
+    let input = format!("[\"{long}\"]");
+
+    let mut lexer = ReadLexer::new(input.as_bytes());
+    lexer.set_chunk_size(64);
+    let tokens = lexer.collect::<Result<Vec<_>, _>>().unwrap();
+
+    match &tokens[1] {
+        Token::String(string, span) => {
+            assert_eq!(string.len(), 5 << 20);
+            assert_eq!(span.as_tuple(), (1, input.len() - 1));
+        }
+        token => panic!("expected a string, got {token:?}"),
+    }
+
+    let input = format!(
+        "[1,{}/*{}*/ // {}\n2]",
+        " \n".repeat(2 << 20),
+        "*/".replace('/', " ").repeat(1 << 20),
+        "x".repeat(1 << 20)
+    );
+    let mut lexer = ReadLexer::with_dialect(input.as_bytes(), Dialect::Jsonc);
+    lexer.set_chunk_size(64);
+    assert_eq!(lexer.map(Result::unwrap).count(), 5);
+
+    // quotes escaped across chunk boundaries
+    let input = "['a\\'b', \"c\\\"d\", '\\\\']";
+    let expected = Lexer::with_dialect(input, Dialect::Json5);
+    let expected = debug_tokens(expected.map(Result::unwrap));
+
+    for chunk_size in 1..6 {
+        let mut lexer = ReadLexer::with_dialect(input.as_bytes(), Dialect::Json5);
+        lexer.set_chunk_size(chunk_size);
+
+        let tokens = lexer.map(Result::unwrap);
+        assert_eq!(debug_tokens(tokens), expected, "{chunk_size}");
+    }
+}
+
+#[test]
+fn errors_point_into_the_stream() {
+    let mut lexer = ReadLexer::new("[1,\n  2,\n  tru]".as_bytes());
+    lexer.set_chunk_size(2);
+
+    let err = lexer.find_map(Result::err).unwrap();
+    assert_eq!(err.kind(), &ErrorKind::InvalidLiteral);
+    assert_eq!(err.span().as_tuple(), (11, 14));
+    assert_eq!((err.line(), err.column()), (3, 3));
+
+    let mut lexer = ReadLexer::new(&b"[\"a\xff\"]"[..]);
+    let err = lexer.find_map(Result::err).unwrap();
+    assert_eq!(err.kind(), &ErrorKind::InvalidUtf8);
+}
@@ -0,0 +1,169 @@
+use iovera::{Dialect, ErrorKind, JsonValue, ParseOptions, Parser};
+
+// The kind and span of an error.
+type Reported = (ErrorKind, (usize, usize));
+
+// Parses `input` recovering, giving the tree and every error.
+fn recover(input: &str) -> (JsonValue, Vec<Reported>) {
+    let (value, _, errors) = recover_with_placeholders(input);
+    (value, errors)
+}
+
+// Same, along with the spans of the placeholders.
+fn recover_with_placeholders(input: &str) -> (JsonValue, Vec<(usize, usize)>, Vec<Reported>) {
+    let recovered = Parser::new(input).parse_recovering();
+    let placeholders = recovered
+        .placeholders
+        .iter()
+        .map(|span| span.as_tuple())
+        .collect();
+    let errors = recovered
+        .errors
+        .into_iter()
+        .map(|err| (err.kind().clone(), err.span().as_tuple()))
+        .collect();
+
+    (recovered.value, placeholders, errors)
+}
+
+fn unexpected(found: &'static str, expected: &'static str) -> ErrorKind {
+    ErrorKind::UnexpectedToken { found, expected }
+}
+
+#[test]
+fn valid_input_has_no_errors() {
+    let input = "{\"a\": [1, 2.5, \"x\"], \"b\": {\"c\": null}}";
+    let (value, errors) = recover(input);
+
+    assert!(errors.is_empty());
+    assert_eq!(value, input.parse::<JsonValue>().unwrap());
+}
+
+#[test]
+fn reports_every_error_at_once() {
+    let input = "{\"a\": tru, \"b\": [1, , 3], \"c\" 4, \"d\": \"\\q\", \"e\": 5}";
+    let (value, placeholders, errors) = recover_with_placeholders(input);
+
+    assert_eq!(
+        errors,
+        [
+            (ErrorKind::InvalidLiteral, (6, 9)),
+            (unexpected("`,`", "a value"), (20, 21)),
+            (unexpected("a number", "`:`"), (30, 31)),
+            (
+                ErrorKind::InvalidEscape("unknown escape character"),
+                (39, 41)
+            ),
+        ]
+    );
+
+    // placeholders are plain `null`s, found through their spans
+    assert_eq!(placeholders, [(6, 9), (20, 21), (39, 41)]);
+    assert!(value["a"].is_null());
+    assert_eq!(value["b"][0].as_i64(), Some(1));
+    assert!(value["b"][1].is_null());
+    assert_eq!(value["b"][2].as_i64(), Some(3));
+    assert_eq!(value["c"].as_i64(), Some(4));
+    assert!(value["d"].is_null());
+    assert_eq!(value["e"].as_i64(), Some(5));
+    assert_eq!(
+        iovera::to_string(&value),
+        "{\"a\":null,\"b\":[1,null,3],\"c\":4,\"d\":null,\"e\":5}"
+    );
+}
+
+#[test]
+fn resynchronizes_at_the_next_entry() {
+    // a broken key skips its whole entry, nested containers included
+    let (value, errors) = recover("{\"a\": 1, null: [3, {\"x\": 4}], \"b\": 5}");
+    assert_eq!(errors, [(unexpected("`null`", "a string key"), (9, 13))]);
+    assert_eq!(value.as_object().unwrap().len(), 2);
+    assert_eq!(value["b"].as_i64(), Some(5));
+
+    // forgotten commas
+    let (value, errors) = recover("[1 2 {\"a\": 1 \"b\": 2}]");
+    assert_eq!(
+        errors,
+        [
+            (unexpected("a number", "`,` or `]`"), (3, 4)),
+            (unexpected("`{`", "`,` or `]`"), (5, 6)),
+            (unexpected("a string", "`,` or `}`"), (13, 16)),
+        ]
+    );
+    assert_eq!(value[2]["b"].as_i64(), Some(2));
+
+    // a trailing comma leaves no placeholder
+    let (value, placeholders, errors) = recover_with_placeholders("[1, 2,]");
+    assert_eq!(errors, [(unexpected("`]`", "a value"), (6, 7))]);
+    assert!(placeholders.is_empty());
+    assert_eq!(value.as_array().unwrap().len(), 2);
+}
+
+#[test]
+fn unclosed_containers_and_strings() {
+    let (value, errors) = recover("{\"a\": [1, 2");
+    assert_eq!(
+        errors,
+        [
+            (ErrorKind::UnexpectedEof("`,` or `]`"), (11, 11)),
+            (ErrorKind::UnexpectedEof("`,` or `}`"), (11, 11)),
+        ]
+    );
+    assert_eq!(value["a"][1].as_i64(), Some(2));
+
+    // a raw line break ends the broken string, the next line is parsed as usual
+    let (value, placeholders, errors) = recover_with_placeholders("[\"abc\n, 1]");
+    assert_eq!(errors, [(ErrorKind::ControlCharacter('\n'), (5, 6))]);
+    assert_eq!(placeholders, [(5, 6)]);
+    assert!(value[0].is_null());
+    assert_eq!(value[1].as_i64(), Some(1));
+
+    // the wrong closing bracket still closes
+    let (_, errors) = recover("[1}");
+    assert_eq!(errors, [(unexpected("`}`", "`,` or `]`"), (2, 3))]);
+
+    let (value, placeholders, errors) = recover_with_placeholders("");
+    assert_eq!(errors, [(ErrorKind::UnexpectedEof("a value"), (0, 0))]);
+    assert_eq!(placeholders, [(0, 0)]);
+    assert!(value.is_null());
+
+    let (_, errors) = recover("1 2 3");
+    assert_eq!(
+        errors,
+        [(
+            unexpected("a number", "end of input after the root value"),
+            (2, 3)
+        )]
+    );
+}
+
+#[test]
+fn limits_stop_everything() {
+    let options = ParseOptions::new().max_depth(2).dialect(Dialect::Jsonc);
+    let recovered = Parser::with_options("[1, [[2]], tru, [3]]", options).parse_recovering();
+    let (value, errors) = (recovered.value, recovered.errors);
+
+    let kinds: Vec<_> = errors.iter().map(|err| err.kind().clone()).collect();
+    assert_eq!(kinds, [ErrorKind::DepthLimitExceeded(2)]);
+    assert_eq!(value[0].as_i64(), Some(1));
+}
+
+#[test]
+fn every_error_has_its_line_and_column() {
+    // a bad literal and a bad escape on every line, the first line opening the outer array
+    let lines: String = (0..500).map(|_| "[tru, \"\\q\"],\n").collect();
+    let input = format!("[{lines}1]");
+
+    let errors = Parser::new(&input).parse_recovering().errors;
+    assert_eq!(errors.len(), 1000);
+
+    for (line, pair) in (1..).zip(errors.chunks(2)) {
+        let shift = match line {
+            1 => 1,
+            _ => 0,
+        };
+
+        assert_eq!((pair[0].line(), pair[0].column()), (line, 2 + shift));
+        assert_eq!((pair[1].line(), pair[1].column()), (line, 8 + shift));
+    }
+}
@@ -0,0 +1,218 @@
+use iovera::{
+    JsonSerialize, JsonValue, SerializeOptions, Serializer, to_string, to_string_with,
+    to_writer_with,
+};
+
+fn sample() -> JsonValue {
+    "{\"name\": \"x\", \"list\": [1, 2.5, null, [], {}], \"nested\": {\"b\": true, \"a\": [{\"d\": 1, \"c\": 2}]}}"
+        .parse()
+        .unwrap()
+}
+
+#[test]
+fn compact_is_minimal() {
+    let value = sample();
+    let compact = to_string(&value);
+
+    assert_eq!(
+        compact,
+        "{\"name\":\"x\",\"list\":[1,2.5,null,[],{}],\"nested\":{\"b\":true,\"a\":[{\"d\":1,\"c\":2}]}}"
+    );
+    assert_eq!(compact.parse::<JsonValue>().unwrap(), value);
+}
+
+#[test]
+fn pretty_layout() {
+    let expected = r#"{
+  "name": "x",
+  "list": [
+    1,
+    2.5,
+    null,
+    [],
+    {}
+  ],
+  "nested": {
+    "b": true,
+    "a": [
+      {
+        "d": 1,
+        "c": 2
+      }
+    ]
+  }
+}"#;
+
+    assert_eq!(
+        to_string_with(&sample(), &SerializeOptions::pretty()).unwrap(),
+        expected
+    );
+
+    let options = SerializeOptions::compact()
+        .indent("\t")
+        .newline("\r\n")
+        .space_after_colon(false);
+    let value: JsonValue = "{\"a\": [1], \"b\": {}}".parse().unwrap();
+
+    assert_eq!(
+        to_string_with(&value, &options).unwrap(),
+        "{\r\n\t\"a\":[\r\n\t\t1\r\n\t],\r\n\t\"b\":{}\r\n}"
+    );
+
+    // a space after colons is enough to ask for on a single line
+    let options = SerializeOptions::compact().space_after_colon(true);
+    assert_eq!(
+        to_string_with(&value, &options).unwrap(),
+        "{\"a\": [1],\"b\": {}}"
+    );
+}
+
+#[test]
+fn sorted_keys_at_every_depth() {
+    let options = SerializeOptions::compact().sort_keys(true);
+    assert_eq!(
+        to_string_with(&sample(), &options).unwrap(),
+        "{\"list\":[1,2.5,null,[],{}],\"name\":\"x\",\"nested\":{\"a\":[{\"c\":2,\"d\":1}],\"b\":true}}"
+    );
+
+    // derived types are sorted too, in a pretty layout
+    #[derive(JsonSerialize)]
+    struct Server {
+        port: u16,
+        host: String,
+        tags: Vec<String>,
+    }
+
+    let server = Server {
+        port: 80,
+        host: "a".to_string(),
+        tags: vec!["z".to_string()],
+    };
+
+    assert_eq!(
+        to_string_with(&server, &SerializeOptions::pretty().sort_keys(true)).unwrap(),
+        "{\n  \"host\": \"a\",\n  \"port\": 80,\n  \"tags\": [\n    \"z\"\n  ]\n}"
+    );
+}
+
+#[test]
+fn escaping() {
+    let string = "quote \" backslash \\ nl \n tab \t bell \u{7} <a href=\"x\">&</a> é 😀";
+
+    assert_eq!(
+        to_string(string),
+        "\"quote \\\" backslash \\\\ nl \\n tab \\t bell \\u0007 <a href=\\\"x\\\">&</a> é 😀\""
+    );
+
+    let options = SerializeOptions::compact().ascii_only(true);
+    assert_eq!(
+        to_string_with("é 😀 \u{2028}", &options).unwrap(),
+        "\"\\u00e9 \\ud83d\\ude00 \\u2028\""
+    );
+
+    let options = SerializeOptions::compact().escape_html(true);
+    assert_eq!(
+        to_string_with("</script><b>&", &options).unwrap(),
+        "\"\\u003c/script\\u003e\\u003cb\\u003e\\u0026\""
+    );
+
+    // escaped text reads back the same
+    let options = SerializeOptions::pretty()
+        .ascii_only(true)
+        .escape_html(true);
+    let written = to_string_with(string, &options).unwrap();
+    assert_eq!(written.parse::<JsonValue>().unwrap().as_str(), Some(string));
+}
+
+#[test]
+fn fmt_and_io_sinks_agree() {
+    let options = SerializeOptions::pretty().sort_keys(true);
+    let value = sample();
+
+    let mut string = String::new();
+    value
+        .serialize(&mut Serializer::with_options(&mut string, options.clone()))
+        .unwrap();
+
+    let mut bytes = Vec::new();
+    to_writer_with(&mut bytes, &value, &options).unwrap();
+
+    assert_eq!(string, String::from_utf8(bytes).unwrap());
+    assert_eq!(string, to_string_with(&value, &options).unwrap());
+}
+
+#[test]
+fn smart_layout_fits_lines_to_the_width() {
+    let value: JsonValue = r#"{"name": "x", "point": [1, 2], "line": [[0, 0], [10, 20]], "tags": ["alpha", "beta", "gamma", "delta"], "empty": {}}"#
+        .parse()
+        .unwrap();
+
+    let expected = r#"{
+  "name": "x",
+  "point": [1, 2],
+  "line": [[0, 0], [10, 20]],
+  "tags": [
+    "alpha",
+    "beta",
+    "gamma",
+    "delta"
+  ],
+  "empty": {}
+}"#;
+    assert_eq!(
+        to_string_with(&value, &SerializeOptions::smart(30)).unwrap(),
+        expected
+    );
+
+    // the trailing comma counts, and everything fits on one line given the room
+    let line = to_string_with(&value, &SerializeOptions::smart(28)).unwrap();
+    assert!(line.contains("\"line\": [\n    [0, 0],\n    [10, 20]\n  ],"));
+    assert_eq!(
+        to_string_with(&value, &SerializeOptions::smart(200)).unwrap(),
+        to_string_with(&value, &SerializeOptions::compact().space_after_colon(true))
+            .unwrap()
+            .replace(",", ", ")
+    );
+
+    // scalars and keys sorted the same as elsewhere
+    assert_eq!(
+        to_string_with(&1.5, &SerializeOptions::smart(10)).unwrap(),
+        to_string(&1.5)
+    );
+    let sorted = to_string_with(&value, &SerializeOptions::smart(30).sort_keys(true)).unwrap();
+    assert!(sorted.starts_with("{\n  \"empty\": {},\n  \"line\": [[0, 0], [10, 20]],"));
+    assert_eq!(sorted.parse::<JsonValue>().unwrap(), value);
+}
+
+#[test]
+fn smart_layout_rules() {
+    let value: JsonValue =
+        r#"{"tags": ["alpha", "beta", "gamma", "delta"], "line": [[0, 0], [10, 20]]}"#
+            .parse()
+            .unwrap();
+
+    // arrays of scalars fill their lines
+    let options = SerializeOptions::smart(30).fill_scalar_arrays(true);
+    assert_eq!(
+        to_string_with(&value, &options).unwrap(),
+        "{\n  \"tags\": [\n    \"alpha\", \"beta\", \"gamma\",\n    \"delta\"\n  ],\n  \"line\": [[0, 0], [10, 20]]\n}"
+    );
+
+    // only containers of scalars on one line
+    let options = SerializeOptions::smart(80).max_inline_depth(1);
+    assert_eq!(
+        to_string_with(&value, &options).unwrap(),
+        "{\n  \"tags\": [\"alpha\", \"beta\", \"gamma\", \"delta\"],\n  \"line\": [\n    [0, 0],\n    [10, 20]\n  ]\n}"
+    );
+
+    // nothing on one line is the pretty layout
+    let options = SerializeOptions::smart(80).max_inline_depth(0);
+    assert_eq!(
+        to_string_with(&value, &options).unwrap(),
+        to_string_with(&value, &SerializeOptions::pretty()).unwrap()
+    );
+
+    // without an indent the width means nothing
+    let options = SerializeOptions::compact().max_width(5);
+    assert_eq!(to_string_with(&value, &options).unwrap(), to_string(&value));
+}
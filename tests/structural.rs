@@ -0,0 +1,91 @@
+use iovera::{Dialect, Lexer};
+
+mod common;
+use common::Rng;
+
+// Strict JSON is lexed by jumping between the token starts of the structural index,
+// JSONC still walks every character, without comments both have to agree.
+fn assert_same_tokens(input: &str) {
+    let strict: Vec<_> = Lexer::new(input)
+        .map(|token| format!("{token:?}"))
+        .collect();
+    let walked: Vec<_> = Lexer::with_dialect(input, Dialect::Jsonc)
+        .map(|token| format!("{token:?}"))
+        .collect();
+
+    assert_eq!(strict, walked, "{input:?}");
+}
+
+const PIECES: &[&str] = &[
+    "{",
+    "}",
+    "[",
+    "]",
+    ":",
+    ",",
+    "\"\"",
+    "\"plain\"",
+    "\"\\\"\"",
+    "\"\\\\\"",
+    "\"\\\\\\\"\\\\\"",
+    "\"a\\nb\\u00e9\"",
+    "\"ünïcödé\"",
+    "\"[{:,}]\"",
+    "0",
+    "-12",
+    "3.5e-7",
+    "true",
+    "false",
+    "null",
+    "tru",
+    "nul",
+    "1.",
+    "01",
+    "\"\t\"",
+    " ",
+    "\n",
+    "\r\n",
+    "\t",
+];
+
+#[test]
+fn jumps_land_where_walking_does() {
+    let mut rng = Rng(0x9E37_79B9_7F4A_7C15);
+
+    for _ in 0..2000 {
+        let mut input = String::new();
+        let len = rng.below(120);
+
+        for _ in 0..len {
+            match rng.below(8) {
+                // long runs push tokens across block boundaries
+                0 => input.push_str(&" ".repeat(rng.below(70))),
+                1 => input.push_str(&format!("\"{}\"", "\\\\".repeat(rng.below(40)))),
+                2 => input.push_str(&format!("\"{}\"", "x".repeat(rng.below(100)))),
+                _ => input.push_str(rng.pick(PIECES)),
+            }
+        }
+
+        assert_same_tokens(&input);
+    }
+}
+
+#[test]
+fn unterminated_and_escaped_quotes() {
+    for prefix in ["", &" ".repeat(60), &"x".repeat(63)] {
+        for rest in [
+            "\"abc",
+            "\"ab\\\"",
+            "[\"a\\\\\", \"b\"]",
+            "[\"a\\\\\\\"\", 1]",
+            "\"\\",
+            "[1 2]",
+            "truefalse",
+            "null\"a\"",
+            "\"a\"1",
+        ] {
+            assert_same_tokens(&format!("[{prefix:?}, {rest}"));
+            assert_same_tokens(&format!("{prefix}{rest}"));
+        }
+    }
+}
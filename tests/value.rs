@@ -0,0 +1,94 @@
+use iovera::{JsonValue, Map};
+
+#[test]
+fn index_and_accessors() {
+    let value: JsonValue = "{\"a\": [true, \"str\"], \"b\": null}".parse().unwrap();
+
+    assert_eq!(value["a"][0].as_bool(), Some(true));
+    assert_eq!(value["a"][1].as_str(), Some("str"));
+    assert!(value["b"].is_null());
+
+    // misses give the shared `Null`
+    assert!(value["missing"][3]["deeper"].is_null());
+}
+
+#[test]
+fn building_documents() {
+    let mut value = JsonValue::Null;
+    value["name"] = "iovera".into();
+    value["tags"] = vec![JsonValue::from(1), JsonValue::from(2.5)].into();
+    *value.entry("count").unwrap().or_insert(0) = 3.into();
+
+    assert_eq!(value["count"].as_i64(), Some(3));
+    assert_eq!(value["tags"][1].as_f64(), Some(2.5));
+    assert_eq!(
+        value.as_object().unwrap(),
+        &Map::from_iter([
+            ("name", JsonValue::from("iovera")),
+            ("tags", value["tags"].clone()),
+            ("count", JsonValue::from(3)),
+        ])
+    );
+}
+
+#[test]
+fn string_escapes() {
+    let value: JsonValue = r#"{"s": ["a\"b", "\\\/\b\f\n\r\t", "\u00e9\uD83D\uDE00", "plain"]}"#
+        .parse()
+        .unwrap();
+    let value = &value["s"];
+
+    assert_eq!(value[0].as_str(), Some("a\"b"));
+    assert_eq!(value[1].as_str(), Some("\\/\u{08}\u{0C}\n\r\t"));
+    assert_eq!(value[2].as_str(), Some("é😀"));
+    assert_eq!(value[3].as_str(), Some("plain"));
+
+    for bad in [
+        r#"{"s": "\uD83D"}"#,
+        r#"{"s": "\uDE00"}"#,
+        r#"{"s": "\x"}"#,
+        "{\"s\": \"tab\there\"}",
+    ] {
+        assert!(bad.parse::<JsonValue>().is_err(), "{bad}");
+    }
+}
+
+#[test]
+fn numbers() {
+    let value: JsonValue = "{\"a\": [-1, 0, 1.5e3, -0.25E-2, 2E+2 ,9223372036854775808], \"b\": 7}"
+        .parse()
+        .unwrap();
+
+    assert_eq!(value["a"][0].as_i64(), Some(-1));
+    assert_eq!(value["a"][1].as_i64(), Some(0));
+    assert_eq!(value["a"][2].as_f64(), Some(1500.0));
+    assert_eq!(value["a"][3].as_f64(), Some(-0.0025));
+    assert_eq!(value["a"][4].as_f64(), Some(200.0));
+    assert_eq!(value["a"][5].as_f64(), Some(9223372036854775808.0));
+    assert_eq!(value["b"].as_i64(), Some(7));
+
+    for bad in ["01", "1.", ".5", "+1", "-", "1e", "1.5x", "-a"] {
+        let input = format!("{{\"a\": {bad}}}");
+        assert!(input.parse::<JsonValue>().is_err(), "{input}");
+    }
+}
+
+#[test]
+fn any_root_value() {
+    let roots = [
+        (" [1, \"a\"] ", JsonValue::from(vec![1.into(), "a".into()])),
+        ("\"str\"", "str".into()),
+        ("\t-12.5\r\n", (-12.5).into()),
+        ("true", true.into()),
+        ("false ", false.into()),
+        ("\nnull", JsonValue::Null),
+    ];
+
+    for (input, expected) in roots {
+        assert_eq!(input.parse::<JsonValue>().unwrap(), expected, "{input:?}");
+    }
+
+    for bad in ["", " \n ", "1 2", "{} []", "nul", "true false"] {
+        assert!(bad.parse::<JsonValue>().is_err(), "{bad:?}");
+    }
+}